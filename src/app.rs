@@ -1,10 +1,36 @@
-use super::cube::gen_scramble;
+use super::cube::{
+    bld::{self, Lettering, LetteringError, Memo},
+    cross::{solve_crosses, CrossSolution},
+    cube2::{self, Inspection},
+    filter::{FilterStats, ScrambleFilter, MAX_MIN_LENGTH},
+    fmc,
+    keymap::{KeyMap, KeyMapError},
+    ll,
+    moves_to_string,
+    notation::{parse_moves, ParseError},
+    pattern::Pattern,
+    recon::{ReconError, Reconstruction},
+    sequence::{simplify, Metric},
+    solve::Solve,
+    solver,
+    subset::Subset,
+    validate::{validate, InvalidState},
+    CubeState, Face, Move, FACELET_ORDER,
+};
+use super::puzzle::{
+    daily::{Day, DAILY_SCRAMBLES},
+    square1::{self, Square1},
+    Puzzle, PuzzleState, Scramble, PUZZLES,
+};
 use ordered_float::*;
 use std::{
     error::Error,
     fmt::{self, Formatter},
     fs,
-    path::Path,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread,
     time::{Duration, Instant},
 };
 use tui::{
@@ -55,27 +81,71 @@ pub enum ActiveBlock {
     Main,
 }
 
-#[derive(Clone, Copy)]
+/// The result of an attempt that doesn't count, like an FMC attempt given
+/// up or out of time. Being slower than any time, it is trimmed off an
+/// average of five like the worst result, and two of them, or one in a mean
+/// of three, make that average a DNF too, as WCA rules have it.
+pub const DNF: f64 = f64::INFINITY;
+
+#[derive(Clone)]
 pub struct Time {
     pub time: f64,
+    /// Mean of the last three, the average used in fewest moves.
+    pub mo3: Option<f64>,
     pub ao5: Option<f64>,
     pub ao12: Option<f64>,
+    /// The scramble solved, for times recorded since scrambles are kept.
+    pub scramble: Option<String>,
+    pub recon: Option<Reconstruction>,
 }
 
 impl Time {
     pub fn from(time: f64) -> Self {
         Self {
             time,
+            mo3: None,
             ao5: None,
             ao12: None,
+            scramble: None,
+            recon: None,
+        }
+    }
+
+    /// Reads a line of a times file: the time, then optionally the scramble
+    /// and a reconstruction, separated by tabs.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split('\t');
+        let mut time = Time::from(match fields.next()? {
+            "DNF" => DNF,
+            v => v.parse().ok()?,
+        });
+        time.scramble = fields.next().filter(|v| !v.is_empty()).map(String::from);
+        time.recon = fields.next().and_then(|v| Reconstruction::parse(v).ok());
+        Some(time)
+    }
+
+    /// The line `parse` reads back.
+    pub fn to_line(&self) -> String {
+        let mut line = self.to_string();
+        if self.scramble.is_some() || self.recon.is_some() {
+            line += &format!("\t{}", self.scramble.as_deref().unwrap_or(""));
+        }
+        if let Some(v) = &self.recon {
+            line += &format!("\t{}", v);
         }
+        line
     }
 
-    pub fn gen_stats(&mut self, times: &Vec<Time>) {
-        let mut tr = times.clone();
-        tr.push(*self);
-        tr.reverse();
+    pub fn gen_stats(&mut self, times: &[Time]) {
+        // Newest first, only as many as the longest average needs
+        let mut tr: Vec<Time> = times.iter().rev().take(11).cloned().collect();
+        tr.insert(0, self.clone());
 
+        self.mo3 = if tr.len() >= 3 {
+            Some(tr[0..3].iter().map(|v| v.time).sum::<f64>() / 3.0)
+        } else {
+            None
+        };
         self.ao5 = if tr.len() >= 5 {
             let set = &tr[0..5];
             Some(Times::calc_aon(set))
@@ -93,7 +163,10 @@ impl Time {
 
 impl std::fmt::Display for Time {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-        f.write_str(&self.time.to_string())?;
+        match self.time == DNF {
+            true => f.write_str("DNF")?,
+            false => f.write_str(&self.time.to_string())?,
+        }
         Ok(())
     }
 }
@@ -101,12 +174,16 @@ impl std::fmt::Display for Time {
 pub struct Times {
     pub times: Vec<Time>,
     pub pbsingle: Option<f64>,
+    pub pbmo3: Option<f64>,
     pub pbao5: Option<f64>,
     pub pbao12: Option<f64>,
     pub ao100: Option<f64>,
     pub ao1k: Option<f64>,
+    /// Mean of the finished solves, leaving out DNFs.
     pub rollingavg: Option<f64>,
     pub sum: f64,
+    /// Solves that aren't DNFs, which `sum` adds up.
+    pub finished: usize,
     pub worst: f64,
 }
 
@@ -115,46 +192,69 @@ impl Times {
         Self {
             times: vec![],
             pbsingle: None,
+            pbmo3: None,
             pbao5: None,
             pbao12: None,
             ao100: None,
             ao1k: None,
             rollingavg: None,
             sum: 0.0,
+            finished: 0,
             worst: 0.0,
         }
     }
 
+    /// Times in the order they were done, with their averages and the
+    /// session's bests worked out in that order.
+    pub fn from_times(times: Vec<Time>) -> Self {
+        let mut result = Times::new();
+        for mut time in times {
+            time.gen_stats(&result.times);
+            result.insert(time);
+        }
+        result
+    }
+
+    /// Removes time `i`, working out the averages after it and the bests
+    /// again without it.
+    pub fn remove(&mut self, i: usize) {
+        let mut times = std::mem::take(&mut self.times);
+        times.remove(i);
+        *self = Times::from_times(times);
+    }
+
     pub fn insert(&mut self, time: Time) {
-        self.times.push(time);
         Times::update_best(&mut self.pbsingle, Some(time.time));
+        Times::update_best(&mut self.pbmo3, time.mo3);
         Times::update_best(&mut self.pbao5, time.ao5);
         Times::update_best(&mut self.pbao12, time.ao12);
+        let single = time.time;
+        self.times.push(time);
 
-        if self.times.len() >= 100 {
-            let mut tr = self.times.clone();
-            tr.reverse();
-            self.ao100 = Some(Times::calc_aon(&tr[0..100]));
-            if self.times.len() >= 1000 {
-                self.ao1k = Some(Times::calc_aon(&tr[0..1000]));
+        let len = self.times.len();
+        if len >= 100 {
+            self.ao100 = Some(Times::calc_aon(&self.times[len - 100..]));
+            if len >= 1000 {
+                self.ao1k = Some(Times::calc_aon(&self.times[len - 1000..]));
             }
         }
 
-        self.sum += time.time;
-
-        self.rollingavg = match self.rollingavg {
-            Some(_) => Some(self.sum / self.times.len() as f64),
-            None => Some(time.time),
-        };
-        if time.time > self.worst {
-            self.worst = time.time;
+        if single == DNF {
+            return;
+        }
+        self.sum += single;
+        self.finished += 1;
+        self.rollingavg = Some(self.sum / self.finished as f64);
+        if single > self.worst {
+            self.worst = single;
         }
     }
 
+    /// Keeps the better of the two, where a DNF is never a best.
     fn update_best(curr: &mut Option<f64>, t: Option<f64>) {
         let new = match t {
-            Some(x) => x,
-            None => return,
+            Some(x) if x != DNF => x,
+            _ => return,
         };
 
         match curr {
@@ -178,17 +278,15 @@ impl Times {
         t.pop();
         t.remove(0);
 
-        let mut sum = OrderedFloat(0.0);
-        let _ = t.iter().map(|v| sum += v).collect::<Vec<()>>();
+        let sum: OrderedFloat<f64> = t.iter().copied().sum();
         sum.into_inner() / t.len() as f64
     }
 
-    pub fn iter(&self) -> TimesIterator {
+    pub fn iter(&self) -> TimesIterator<'_> {
         TimesIterator {
             curr: 0,
             times: &self.times,
         }
-        .into_iter()
     }
 }
 
@@ -201,7 +299,7 @@ impl<'a> Iterator for TimesIterator<'a> {
     type Item = Time;
     fn next(&mut self) -> Option<Self::Item> {
         self.curr += 1;
-        self.times.get(self.curr).copied()
+        self.times.get(self.curr).cloned()
     }
 }
 
@@ -231,6 +329,12 @@ impl CubeTimer {
         }
     }
 
+    /// Stops the timer without a time.
+    pub fn reset(&mut self) {
+        self.on = false;
+        self.starttime = None;
+    }
+
     fn timer_on(&mut self) {
         self.on = true;
         self.starttime = Some(Instant::now());
@@ -267,11 +371,407 @@ impl CubeTimer {
     }
 }
 
+/// How long the Solver tool keeps looking for shorter solutions.
+const SOLVER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// State of the Solver tool. Searches run on a background thread and their
+/// result is picked up on tick.
+pub struct Solver {
+    pub input: String,
+    pub custom: Option<CubeState>,
+    /// The entered scramble with redundant moves cancelled, if the custom
+    /// cube was entered as one.
+    pub entered: Option<Vec<Move>>,
+    pub solution: Option<Vec<Move>>,
+    pub error: Option<String>,
+    pending: Option<Receiver<Vec<Move>>>,
+}
+
+impl Solver {
+    fn default() -> Self {
+        Self {
+            input: String::new(),
+            custom: None,
+            entered: None,
+            solution: None,
+            error: None,
+            pending: None,
+        }
+    }
+
+    pub fn searching(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    fn start(&mut self, state: &CubeState) {
+        self.solution = None;
+        let cube = match validate(state) {
+            Ok(v) => v,
+            Err(e) => {
+                self.error = Some(format!("Not a solvable cube: {}", e));
+                self.pending = None;
+                return;
+            }
+        };
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            // The receiver is gone if a newer search replaced this one
+            let _ = tx.send(solver::solve_near_optimal(&cube, SOLVER_TIMEOUT));
+        });
+        self.pending = Some(rx);
+    }
+
+    fn poll(&mut self) {
+        if let Some(rx) = &self.pending {
+            if let Ok(v) = rx.try_recv() {
+                self.solution = Some(v);
+                self.pending = None;
+            }
+        }
+    }
+}
+
+/// Time allowed for an FMC attempt.
+const FMC_TIME_LIMIT: Duration = Duration::from_secs(60 * 60);
+
+/// State of the FMC tool: the countdown of the running attempt, the
+/// solution being typed and the last accepted one.
+pub struct Fmc {
+    pub input: String,
+    started: Option<Instant>,
+    pub error: Option<String>,
+    pub solution: Option<Vec<Move>>,
+}
+
+impl Fmc {
+    fn default() -> Self {
+        Self {
+            input: String::new(),
+            started: None,
+            error: None,
+            solution: None,
+        }
+    }
+
+    /// Time left in the running attempt, if there is one.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.started
+            .map(|v| FMC_TIME_LIMIT.saturating_sub(v.elapsed()))
+    }
+
+    /// Time left shown as minutes and seconds, a full hour between
+    /// attempts.
+    pub fn countdown(&self) -> String {
+        let secs = self.remaining().unwrap_or(FMC_TIME_LIMIT).as_secs_f64().ceil() as u64;
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// State of the Virtual cube tool: the cube being turned with the keyboard
+/// and the session's solves on it.
+pub struct VirtualCube {
+    pub keymap: KeyMap,
+    /// The scrambled cube as turned so far, or `None` if the puzzle is not
+    /// a 3x3.
+    pub state: Option<CubeState>,
+    pub moves: Vec<Move>,
+    /// Whether keys go to the key map being typed instead of the cube.
+    pub editing: bool,
+    pub input: String,
+    pub error: Option<KeyMapError>,
+    pub solves: Vec<Solve>,
+}
+
+impl VirtualCube {
+    fn default() -> Self {
+        Self {
+            keymap: KeyMap::default(),
+            state: None,
+            moves: vec![],
+            editing: false,
+            input: String::new(),
+            error: None,
+            solves: vec![],
+        }
+    }
+}
+
+/// Width and height of the Cube tool's net in stickers: U above L F R B,
+/// then D.
+pub const NET_SIZE: (usize, usize) = (12, 9);
+
+/// The facelet at a row and column of the net, if there is one there.
+pub fn net_facelet(row: usize, col: usize) -> Option<usize> {
+    let face = match (row / 3, col / 3) {
+        (0, 1) => Face::U,
+        (1, 0) => Face::L,
+        (1, 1) => Face::F,
+        (1, 2) => Face::R,
+        (1, 3) => Face::B,
+        (2, 1) => Face::D,
+        _ => return None,
+    };
+    let i = FACELET_ORDER.iter().position(|v| *v == face)?;
+    Some(i * 9 + (row % 3) * 3 + col % 3)
+}
+
+/// Face whose color a painting key stands for, in the standard color
+/// scheme.
+fn paint_color(c: char) -> Option<Face> {
+    match c {
+        'w' => Some(Face::U),
+        'y' => Some(Face::D),
+        'g' => Some(Face::F),
+        'b' => Some(Face::B),
+        'r' => Some(Face::R),
+        'o' => Some(Face::L),
+        _ => None,
+    }
+}
+
+/// Whether typed input is meant as a facelet string rather than a scramble:
+/// only letters, with a run of nine or more, which no scramble written with
+/// spaces or primes has.
+fn looks_like_facelets(s: &str) -> bool {
+    s.chars().all(|v| v.is_ascii_alphabetic() || v.is_whitespace())
+        && s.split_whitespace().any(|v| v.len() >= 9)
+}
+
+/// How the Cube tool draws the cube.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CubeView {
+    /// The flat cross-shaped net, the only view that can be painted.
+    Net,
+    /// U, F and R seen from above the front right corner.
+    Front,
+    /// The cube turned over to show D, L and B.
+    Back,
+}
+
+impl CubeView {
+    fn next(self) -> Self {
+        match self {
+            CubeView::Net => CubeView::Front,
+            CubeView::Front => CubeView::Back,
+            CubeView::Back => CubeView::Net,
+        }
+    }
+}
+
+/// State of the Cube tool's painter, for entering a real cube sticker by
+/// sticker, or a Square-1 by typing its scramble.
+pub struct Painter {
+    /// Row and column of the net the cursor is on.
+    pub cursor: (usize, usize),
+    pub view: CubeView,
+    /// The painted cube, or `None` while showing the scramble.
+    pub state: Option<CubeState>,
+    /// Result of the last check of the painted cube.
+    pub checked: Option<Result<(), InvalidState>>,
+    /// The Square-1 scramble being typed, the state the last one entered
+    /// leads to and why the last one couldn't be read.
+    pub input: String,
+    pub square1: Option<Square1>,
+    pub error: Option<square1::ParseError>,
+}
+
+impl Painter {
+    fn default() -> Self {
+        Self {
+            cursor: (4, 4),
+            view: CubeView::Net,
+            state: None,
+            checked: None,
+            input: String::new(),
+            square1: None,
+            error: None,
+        }
+    }
+
+    /// Moves the cursor one sticker, jumping over the gaps in the net.
+    fn mv(&mut self, dir: Dir) {
+        let (mut row, mut col) = self.cursor;
+        loop {
+            match dir {
+                Dir::Up if row > 0 => row -= 1,
+                Dir::Down if row + 1 < NET_SIZE.1 => row += 1,
+                Dir::Left if col > 0 => col -= 1,
+                Dir::Right if col + 1 < NET_SIZE.0 => col += 1,
+                _ => return,
+            }
+            if net_facelet(row, col).is_some() {
+                self.cursor = (row, col);
+                return;
+            }
+        }
+    }
+
+    fn paint(&mut self, face: Face, scrambled: CubeState) {
+        let state = self.state.get_or_insert(scrambled);
+        if let Some(i) = net_facelet(self.cursor.0, self.cursor.1) {
+            state.facelets[i] = face;
+        }
+        self.checked = None;
+    }
+}
+
+/// State of the Memo tool: the lettering scheme and a new one being typed.
+pub struct MemoTool {
+    pub input: String,
+    pub lettering: Lettering,
+    pub error: Option<LetteringError>,
+}
+
+impl MemoTool {
+    fn default() -> Self {
+        Self {
+            input: String::new(),
+            lettering: Lettering::default(),
+            error: None,
+        }
+    }
+}
+
+/// State of the Reconstruction tool: a reconstruction being typed for the
+/// selected solve.
+pub struct ReconTool {
+    pub input: String,
+    pub error: Option<String>,
+}
+
+impl ReconTool {
+    fn default() -> Self {
+        Self {
+            input: String::new(),
+            error: None,
+        }
+    }
+}
+
+/// State of the Last layer tool: the cube whose case is shown when it
+/// isn't the scramble's, and what was typed to get it.
+pub struct LastLayer {
+    pub input: String,
+    /// What the entered cube is and the cube itself.
+    pub entered: Option<(String, CubeState)>,
+    pub error: Option<ParseError>,
+}
+
+impl LastLayer {
+    fn default() -> Self {
+        Self {
+            input: String::new(),
+            entered: None,
+            error: None,
+        }
+    }
+}
+
+/// State of the Filter tool: whether random 3x3 scrambles are filtered,
+/// how, and what the filter has turned down so far. Checking a cube can
+/// take the solver a while, so scrambles are looked for on a background
+/// thread and picked up on tick.
+pub struct FilterTool {
+    pub enabled: bool,
+    pub filter: ScrambleFilter,
+    pub stats: FilterStats,
+    pub input: String,
+    pending: Option<Receiver<(Scramble, FilterStats)>>,
+}
+
+impl FilterTool {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            filter: ScrambleFilter::default(),
+            stats: FilterStats::default(),
+            input: String::new(),
+            pending: None,
+        }
+    }
+
+    pub fn searching(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Starts looking for a scramble of `puzzle` that passes the filter,
+    /// counting the cubes checked on top of the ones so far.
+    fn start(&mut self, puzzle: Puzzle) {
+        let (filter, mut stats) = (self.filter, self.stats);
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let scramble = puzzle.filtered_scramble(&mut rand::thread_rng(), &filter, &mut stats);
+            // The receiver is gone if a newer scramble replaced this one
+            let _ = tx.send((scramble, stats));
+        });
+        self.pending = Some(rx);
+    }
+
+    /// The scramble found, once there is one.
+    fn poll(&mut self) -> Option<Scramble> {
+        let (scramble, stats) = self.pending.as_ref()?.try_recv().ok()?;
+        self.stats = stats;
+        self.pending = None;
+        Some(scramble)
+    }
+}
+
+/// State of the Patterns tool: the built-in patterns followed by the
+/// user's, the one shown and a new one being typed.
+pub struct Patterns {
+    pub list: Vec<Pattern>,
+    pub state: ListState,
+    pub input: String,
+    pub error: Option<ParseError>,
+}
+
+impl Patterns {
+    fn default() -> Self {
+        let mut state = ListState::default();
+        state.select(Some(0));
+        Self {
+            list: Pattern::builtin(),
+            state,
+            input: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn selected(&self) -> Option<&Pattern> {
+        self.list.get(self.state.selected().unwrap_or(0))
+    }
+
+    /// Name for the next pattern entered without one.
+    fn next_name(&self) -> String {
+        format!("Custom {}", self.list.len() - Pattern::builtin().len() + 1)
+    }
+
+    fn mv(&mut self, dir: Dir) {
+        let (i, n) = (self.state.selected().unwrap_or(0), self.list.len());
+        let i = match dir {
+            Dir::Up => (i + n - 1) % n,
+            Dir::Down => (i + 1) % n,
+            Dir::Left | Dir::Right => i,
+        };
+        self.state.select(Some(i));
+    }
+}
+
 #[derive(Copy, Clone)]
 pub enum Tool {
     Welcome,
     Chart,
     Cube,
+    Solver,
+    Cross,
+    Memo,
+    Fmc,
+    Filter,
+    Patterns,
+    Virtual,
+    Recon,
+    LastLayer,
+    Cube2,
 }
 
 impl fmt::Display for Tool {
@@ -280,6 +780,16 @@ impl fmt::Display for Tool {
             Tool::Welcome => "Welcome",
             Tool::Chart => "Chart",
             Tool::Cube => "Cube",
+            Tool::Solver => "Solver",
+            Tool::Cross => "Cross",
+            Tool::Memo => "Memo",
+            Tool::Fmc => "FMC",
+            Tool::Filter => "Filter",
+            Tool::Patterns => "Patterns",
+            Tool::Virtual => "Virtual cube",
+            Tool::Recon => "Reconstruction",
+            Tool::LastLayer => "Last layer",
+            Tool::Cube2 => "Inspection",
         };
         write!(f, "{}", text)?;
         Ok(())
@@ -303,9 +813,45 @@ pub struct App<'a> {
     pub times_state: TableState,
     pub tools_state: ListState,
     layout: Vec<Vec<ActiveBlock>>,
-    pub scramble: String,
+    pub puzzle: Puzzle,
+    pub scramble: Scramble,
+    pub state: Option<PuzzleState>,
     pub tools: Vec<Tool>,
     pub active_tool: Tool,
+    pub solver: Solver,
+    pub painter: Painter,
+    /// How many moves of the scramble the Cube tool shows done while
+    /// stepping through it, or `None` for the whole scramble.
+    pub playback: Option<usize>,
+    /// Day of the daily set being done and the index of the current
+    /// scramble in it, or `None` for random scrambles.
+    pub daily: Option<(Day, usize)>,
+    /// Optimal crosses of the current 3x3 scramble.
+    pub crosses: Option<Vec<CrossSolution>>,
+    /// The scramble before the current one and its optimal crosses, to
+    /// check right after a solve.
+    pub last_crosses: Option<(Scramble, Vec<CrossSolution>)>,
+    pub memo_tool: MemoTool,
+    /// Blindfolded memo of the current 3x3 scramble.
+    pub memo: Option<Memo>,
+    /// The scramble before the current one and its memo, to check after a
+    /// DNF.
+    pub last_memo: Option<(Scramble, Memo)>,
+    pub fmc: Fmc,
+    pub filter: FilterTool,
+    pub patterns: Patterns,
+    pub virtual_cube: VirtualCube,
+    pub recon: ReconTool,
+    pub last_layer: LastLayer,
+    /// Optimal solution and first layers of the current 2x2 scramble, worked
+    /// out once the Inspection tool is open.
+    pub inspection: Option<Inspection>,
+    /// Optimal solution of the current Pyraminx or Skewb scramble, worked
+    /// out once the Inspection tool is open.
+    pub optimal: Option<Vec<String>>,
+    /// The scramble before the current one and its inspection, if the tool
+    /// was open for it, to check right after a solve.
+    pub last_inspection: Option<(Scramble, Inspection)>,
 }
 
 impl<'a> App<'a> {
@@ -314,6 +860,13 @@ impl<'a> App<'a> {
         let mut tools_state = ListState::default();
         tools_state.select(Some(0));
 
+        let puzzle = Puzzle::Cube3(Subset::Full);
+        let scramble = puzzle.gen_scramble(&mut rand::thread_rng());
+        let state = scramble.state();
+        let crosses = state.as_ref().and_then(|v| v.cube()).and_then(solve_crosses);
+        let memo = state.as_ref().and_then(|v| v.cube()).and_then(bld::trace);
+        let virtual_state = state.as_ref().and_then(|v| v.cube()).copied();
+
         // Construct app
         Ok(App {
             tick_rate,
@@ -328,32 +881,140 @@ impl<'a> App<'a> {
                 vec![ActiveBlock::Tools, ActiveBlock::Timer, ActiveBlock::Times],
                 vec![ActiveBlock::Scramble, ActiveBlock::Stats, ActiveBlock::Main],
             ],
-            scramble: gen_scramble(),
-            tools: vec![Tool::Welcome, Tool::Chart, Tool::Cube],
+            puzzle,
+            scramble,
+            state,
+            tools: vec![
+                Tool::Welcome,
+                Tool::Chart,
+                Tool::Cube,
+                Tool::Solver,
+                Tool::Cross,
+                Tool::Memo,
+                Tool::Fmc,
+                Tool::Filter,
+                Tool::Patterns,
+                Tool::Virtual,
+                Tool::Recon,
+                Tool::LastLayer,
+                Tool::Cube2,
+            ],
             active_tool: Tool::Welcome,
+            solver: Solver::default(),
+            painter: Painter::default(),
+            playback: None,
+            daily: None,
+            crosses,
+            last_crosses: None,
+            memo_tool: MemoTool::default(),
+            memo,
+            last_memo: None,
+            fmc: Fmc::default(),
+            filter: FilterTool::default(),
+            patterns: Patterns::default(),
+            virtual_cube: VirtualCube {
+                state: virtual_state,
+                ..VirtualCube::default()
+            },
+            recon: ReconTool::default(),
+            last_layer: LastLayer::default(),
+            inspection: None,
+            optimal: None,
+            last_inspection: None,
         })
     }
 
+    /// Each puzzle has its own session file. 3x3 times keep the plain
+    /// `times` file, other puzzles' go next to it.
+    fn times_path(&self) -> PathBuf {
+        match self.puzzle {
+            Puzzle::Cube3(Subset::Full) => self.path.to_path_buf(),
+            p => self.path.with_file_name(format!("times-{}", p.id())),
+        }
+    }
+
+    /// Virtual cube solves of the session, kept next to its times.
+    fn solves_path(&self) -> PathBuf {
+        match self.puzzle {
+            Puzzle::Cube3(Subset::Full) => self.path.with_file_name("solves"),
+            p => self.path.with_file_name(format!("solves-{}", p.id())),
+        }
+    }
+
     pub fn load_times(&mut self) -> Result<(), Box<dyn Error>> {
-        let directory = self.path.with_file_name("");
+        let path = self.times_path();
+        let directory = path.with_file_name("");
         fs::create_dir_all(directory)?;
 
         // Create file if it doesn't exist
-        match fs::File::open(&self.path) {
-            Err(_) => _ = fs::File::create(&self.path)?,
-            Ok(_) => (),
-        };
+        if fs::File::open(&path).is_err() {
+            fs::File::create(&path)?;
+        }
 
-        let mut times: Vec<Time> = fs::read_to_string(&self.path)?
+        let times: Vec<Time> = fs::read_to_string(&path)?
             .lines()
-            .filter_map(|v| v.parse::<f64>().ok())
-            .map(|v| Time::from(v))
+            .filter_map(Time::parse)
             .collect();
 
-        self.times = Times::new();
-        for time in &mut times {
-            time.gen_stats(&self.times.times);
-            self.times.insert(*time);
+        self.times = Times::from_times(times);
+
+        let path = self.solves_path();
+        self.virtual_cube.solves = match path.exists() {
+            true => fs::read_to_string(&path)?
+                .lines()
+                .filter_map(Solve::parse)
+                .collect(),
+            false => vec![],
+        };
+        Ok(())
+    }
+
+    /// Reads the lettering scheme saved by the Memo tool, keeping Speffz if
+    /// there is none.
+    pub fn load_lettering(&mut self) -> Result<(), Box<dyn Error>> {
+        let path = self.path.with_file_name("lettering");
+        if path.exists() {
+            if let Ok(v) = Lettering::parse(&fs::read_to_string(&path)?) {
+                self.memo_tool.lettering = v;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the Filter tool's counts of the cubes it has checked, starting
+    /// from none if they aren't saved.
+    pub fn load_filter_stats(&mut self) -> Result<(), Box<dyn Error>> {
+        let path = self.path.with_file_name("filter");
+        if path.exists() {
+            if let Some(v) = FilterStats::parse(&fs::read_to_string(&path)?) {
+                self.filter.stats = v;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the virtual cube's key map, saved as csTimer style bindings,
+    /// keeping csTimer's if there is none.
+    pub fn load_keymap(&mut self) -> Result<(), Box<dyn Error>> {
+        let path = self.path.with_file_name("keys");
+        if path.exists() {
+            if let Ok(v) = KeyMap::parse(&fs::read_to_string(&path)?) {
+                self.virtual_cube.keymap = v;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the patterns saved from the Patterns tool after the built-in
+    /// ones, one `name: moves` per line. Lines that don't read are skipped.
+    pub fn load_patterns(&mut self) -> Result<(), Box<dyn Error>> {
+        let path = self.path.with_file_name("patterns");
+        if path.exists() {
+            for line in fs::read_to_string(&path)?.lines() {
+                if let Ok(v) = Pattern::parse(line, &self.patterns.next_name()) {
+                    self.patterns.list.push(v);
+                }
+            }
         }
         Ok(())
     }
@@ -363,9 +1024,9 @@ impl<'a> App<'a> {
             .times
             .times
             .iter()
-            .flat_map(|v| format!("{}\n", v.to_string()).bytes().collect::<Vec<u8>>())
+            .flat_map(|v| format!("{}\n", v.to_line()).bytes().collect::<Vec<u8>>())
             .collect();
-        fs::write(&self.path, write_data)?;
+        fs::write(self.times_path(), write_data)?;
         Ok(())
     }
 
@@ -384,11 +1045,11 @@ impl<'a> App<'a> {
         let style = Style::default();
 
         if id == self.route.active_block {
-            return style.fg(Color::LightGreen).add_modifier(Modifier::BOLD);
+            style.fg(Color::LightGreen).add_modifier(Modifier::BOLD)
         } else if id == self.route.selected_block {
-            return style.fg(Color::LightBlue).add_modifier(Modifier::BOLD);
+            style.fg(Color::LightBlue).add_modifier(Modifier::BOLD)
         } else {
-            return style.fg(Color::Gray);
+            style.fg(Color::Gray)
         }
     }
 
@@ -396,18 +1057,17 @@ impl<'a> App<'a> {
         let style = Style::default().add_modifier(Modifier::BOLD);
 
         if id == self.route.active_block {
-            return style.fg(Color::LightGreen);
+            style.fg(Color::LightGreen)
         } else if id == self.route.selected_block {
-            return style.fg(Color::LightBlue);
+            style.fg(Color::LightBlue)
         } else {
-            return style.fg(Color::White);
+            style.fg(Color::White)
         }
     }
 
     pub fn del(&mut self) {
-        match self.route.active_block {
-            ActiveBlock::Times => self.del_time(),
-            _ => (),
+        if self.route.active_block == ActiveBlock::Times {
+            self.del_time();
         }
     }
 
@@ -451,7 +1111,7 @@ impl<'a> App<'a> {
     }
 
     fn mv_up(&mut self) {
-        if (self.pos.1) as i32 - 1 >= 0 {
+        if self.pos.1 > 0 {
             self.pos.1 -= 1;
         }
     }
@@ -473,7 +1133,7 @@ impl<'a> App<'a> {
     }
 
     pub fn mv_left(&mut self) {
-        if (self.pos.0) as i32 - 1 >= 0 {
+        if self.pos.0 > 0 {
             self.pos.0 -= 1;
         }
     }
@@ -515,21 +1175,18 @@ impl<'a> App<'a> {
     }
 
     fn del_time(&mut self) {
-        match self.times_state.selected() {
-            Some(v) => {
-                // Edge cases (literally)
-                let len = self.times.times.len();
-                if len <= 0 || v >= len {
-                    return;
-                }
-                self.times.times.remove(len - v - 1);
-                // Go up one if selection fell off
-                if v == self.times.times.len() {
-                    self.previous_time();
-                }
+        if let Some(v) = self.times_state.selected() {
+            // Edge cases (literally)
+            let len = self.times.times.len();
+            if len == 0 || v >= len {
+                return;
             }
-            None => (),
-        };
+            self.times.remove(len - v - 1);
+            // Go up one if selection fell off
+            if v == self.times.times.len() {
+                self.previous_time();
+            }
+        }
     }
 
     pub fn next_tool(&mut self) {
@@ -545,6 +1202,8 @@ impl<'a> App<'a> {
         };
         self.tools_state.select(Some(i));
         self.active_tool = self.tools[self.tools_state.selected().unwrap_or(0)];
+        self.update_solver();
+        self.update_inspection();
     }
 
     fn previous_tool(&mut self) {
@@ -560,13 +1219,656 @@ impl<'a> App<'a> {
         };
         self.tools_state.select(Some(i));
         self.active_tool = self.tools[self.tools_state.selected().unwrap_or(0)];
+        self.update_solver();
+        self.update_inspection();
     }
 
     pub fn new_scramble(&mut self) {
-        self.scramble = gen_scramble();
+        // A filtered scramble still being looked for isn't wanted anymore
+        self.filter.pending = None;
+        let scramble = match self.daily {
+            Some((day, i)) => self.puzzle.daily_scramble(day, i),
+            None if self.filter.enabled && self.puzzle.filtered() => {
+                self.filter.start(self.puzzle);
+                // Ticking often shows the scramble as soon as it is found.
+                // Until then the cube is solved and can't be timed.
+                self.tick_rate = Duration::from_millis(100);
+                Scramble::Cube(vec![])
+            }
+            None => self.puzzle.gen_scramble(&mut rand::thread_rng()),
+        };
+        self.show_scramble(scramble);
+    }
+
+    /// Makes `scramble` the current one, along with what the tools work out
+    /// from it.
+    fn show_scramble(&mut self, scramble: Scramble) {
+        self.scramble = scramble;
+        self.state = self.scramble.state();
+        self.playback = None;
+        self.virtual_cube.state = match self.puzzle {
+            Puzzle::Fmc => None,
+            _ => self.state.as_ref().and_then(|v| v.cube()).copied(),
+        };
+        self.virtual_cube.moves.clear();
+        self.crosses = self
+            .state
+            .as_ref()
+            .and_then(|v| v.cube())
+            .and_then(solve_crosses);
+        self.memo = self.state.as_ref().and_then(|v| v.cube()).and_then(bld::trace);
+        self.inspection = None;
+        self.optimal = None;
+        self.update_inspection();
+        if self.solver.custom.is_none() {
+            self.solver.solution = None;
+            self.solver.error = None;
+            self.solver.pending = None;
+            self.update_solver();
+        }
+    }
+
+    /// Moves on after a solve: to the next scramble of the daily set, or
+    /// back to random scrambles once it is done.
+    pub fn next_scramble(&mut self) {
+        self.last_crosses = self.crosses.take().map(|v| (self.scramble.clone(), v));
+        self.last_memo = self.memo.take().map(|v| (self.scramble.clone(), v));
+        self.last_inspection = self.inspection.take().map(|v| (self.scramble.clone(), v));
+        if let Some((day, i)) = self.daily {
+            self.daily = (i + 1 < DAILY_SCRAMBLES).then_some((day, i + 1));
+        }
+        self.new_scramble();
+    }
+
+    /// Starts today's daily set from its first scramble, or stops doing it.
+    pub fn toggle_daily(&mut self) {
+        self.daily = match self.daily {
+            Some(_) => None,
+            None => Some((Day::today(), 0)),
+        };
+        self.new_scramble();
+    }
+
+    /// Saves the current session and switches to the next puzzle's.
+    pub fn next_puzzle(&mut self) -> Result<(), Box<dyn Error>> {
+        // Any 3x3 subset counts as 3x3
+        let i = PUZZLES
+            .iter()
+            .position(|v| match (v, self.puzzle) {
+                (Puzzle::Cube3(_), Puzzle::Cube3(_)) => true,
+                (v, p) => *v == p,
+            })
+            .unwrap_or(0);
+        self.switch_puzzle(PUZZLES[(i + 1) % PUZZLES.len()])
+    }
+
+    /// Saves the current session and switches 3x3 to its next training
+    /// subset, which keeps a session of its own.
+    pub fn next_subset(&mut self) -> Result<(), Box<dyn Error>> {
+        self.switch_puzzle(self.puzzle.next_subset())
+    }
+
+    fn switch_puzzle(&mut self, puzzle: Puzzle) -> Result<(), Box<dyn Error>> {
+        if puzzle == self.puzzle {
+            return Ok(());
+        }
+        self.write_times()?;
+        self.puzzle = puzzle;
+        self.times_state.select(None);
+        self.last_crosses = None;
+        self.last_memo = None;
+        self.last_inspection = None;
+        self.fmc.started = None;
+        self.painter.input.clear();
+        self.painter.square1 = None;
+        self.painter.error = None;
+        self.load_times()?;
+        if let Some((day, _)) = self.daily {
+            self.daily = Some((day, 0));
+        }
+        self.new_scramble();
+        Ok(())
     }
 
-    pub fn on_tick(&self) {
-        ()
+    /// Adds a time for the current scramble, which it keeps.
+    pub fn record(&mut self, mut time: Time) {
+        time.scramble = Some(self.scramble_text().replace('\n', " "));
+        time.gen_stats(&self.times.times);
+        self.times.insert(time);
+    }
+
+    /// Index of the time the Reconstruction tool works on: the one selected
+    /// in the times table, or the latest.
+    pub fn recon_target(&self) -> Option<usize> {
+        let len = self.times.times.len();
+        match self.times_state.selected() {
+            Some(v) if v < len => Some(len - v - 1),
+            _ => len.checked_sub(1),
+        }
+    }
+
+    pub fn scramble_text(&self) -> String {
+        self.scramble.to_string()
+    }
+
+    /// Works out the inspection of a 2x2 scramble, or the optimal solution
+    /// of a Pyraminx or Skewb one, if the Inspection tool is open and hasn't
+    /// got it yet.
+    fn update_inspection(&mut self) {
+        if !matches!(self.active_tool, Tool::Cube2)
+            || self.inspection.is_some()
+            || self.optimal.is_some()
+        {
+            return;
+        }
+        self.inspection = self.state.as_ref().and_then(|v| v.nxn()).and_then(cube2::inspect);
+        self.optimal = self.state.as_ref().and_then(PuzzleState::optimal_solution);
+    }
+
+    /// Starts solving the Solver tool's cube if it is open and has nothing
+    /// to show yet.
+    fn update_solver(&mut self) {
+        if !matches!(self.active_tool, Tool::Solver)
+            || self.solver.solution.is_some()
+            || self.solver.searching()
+            || self.solver.error.is_some()
+        {
+            return;
+        }
+        let state = match (self.solver.custom, self.state.as_ref().and_then(|v| v.cube())) {
+            (Some(v), _) => v,
+            (None, Some(v)) => *v,
+            (None, None) => {
+                self.solver.error = Some(format!("The solver only works on 3x3, not {}", self.puzzle));
+                return;
+            }
+        };
+        self.solver.start(&state);
+    }
+
+    /// Whether keys go to the open tool instead of being shortcuts: typing
+    /// in the Solver, Memo, FMC or Patterns tool, painting a cube or typing
+    /// a Square-1 scramble in the Cube tool,
+    /// setting up the Filter tool, turning the virtual cube, entering a
+    /// reconstruction or picking a last layer case.
+    pub fn typing(&self) -> bool {
+        self.route.active_block == ActiveBlock::Main
+            && matches!(
+                self.active_tool,
+                Tool::Solver
+                    | Tool::Cube
+                    | Tool::Memo
+                    | Tool::Fmc
+                    | Tool::Filter
+                    | Tool::Patterns
+                    | Tool::Virtual
+                    | Tool::Recon
+                    | Tool::LastLayer
+            )
+    }
+
+    /// Arrow keys while typing: they step through the scramble in the Cube
+    /// tool and pick the pattern in the Patterns tool.
+    pub fn arrow(&mut self, dir: Dir) {
+        match self.active_tool {
+            Tool::Cube => self.step(dir),
+            Tool::Patterns => self.patterns.mv(dir),
+            _ => (),
+        }
+    }
+
+    /// Left undoes the last move shown, right does the next one, starting
+    /// from the whole scramble. Up and down jump to either end.
+    fn step(&mut self, dir: Dir) {
+        let len = match (
+            &self.painter.state,
+            &self.painter.square1,
+            self.scramble.steps(),
+        ) {
+            (None, None, Some(v)) => v.len(),
+            _ => return,
+        };
+        let done = self.playback.unwrap_or(len);
+        let done = match dir {
+            Dir::Left => done.saturating_sub(1),
+            Dir::Right => (done + 1).min(len),
+            Dir::Up => 0,
+            Dir::Down => len,
+        };
+        self.playback = Some(done);
+    }
+
+    pub fn type_char(&mut self, c: char) {
+        match self.active_tool {
+            Tool::Cube => self.paint_key(c),
+            Tool::Memo => self.memo_tool.input.push(c),
+            Tool::Fmc => self.fmc.input.push(c),
+            Tool::Filter => self.filter_key(c),
+            Tool::Patterns => self.patterns.input.push(c),
+            Tool::Virtual if self.virtual_cube.editing => self.virtual_cube.input.push(c),
+            Tool::Virtual => self.turn(c),
+            Tool::Recon => self.recon.input.push(c),
+            Tool::LastLayer => self.last_layer.input.push(c),
+            _ => self.solver.input.push(c),
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        match self.active_tool {
+            Tool::Cube => self.painter.input.pop(),
+            Tool::Memo => self.memo_tool.input.pop(),
+            Tool::Fmc => self.fmc.input.pop(),
+            Tool::Filter => self.filter.input.pop(),
+            Tool::Patterns => self.patterns.input.pop(),
+            Tool::Virtual if self.virtual_cube.editing => self.virtual_cube.input.pop(),
+            Tool::Virtual => {
+                self.restart_virtual();
+                None
+            }
+            Tool::Recon => self.recon.input.pop(),
+            Tool::LastLayer => self.last_layer.input.pop(),
+            _ => self.solver.input.pop(),
+        };
+    }
+
+    pub fn submit(&mut self) {
+        match self.active_tool {
+            Tool::Cube => self.submit_painting(),
+            Tool::Memo => self.submit_lettering(),
+            Tool::Fmc => self.submit_fmc(),
+            Tool::Filter => self.submit_filter(),
+            Tool::Patterns => self.submit_pattern(),
+            Tool::Virtual => self.submit_keymap(),
+            Tool::Recon => self.submit_recon(),
+            Tool::LastLayer => self.submit_last_layer(),
+            _ => self.submit_solver(),
+        }
+    }
+
+    /// Shows the case named, like `OLL 27` or `T`, or the cube the typed
+    /// moves make. Nothing typed goes back to the scramble.
+    fn submit_last_layer(&mut self) {
+        let input = std::mem::take(&mut self.last_layer.input);
+        let tool = &mut self.last_layer;
+        tool.error = None;
+        if input.trim().is_empty() {
+            tool.entered = None;
+        } else if let Some(alg) = ll::named_alg(&input) {
+            tool.entered = Some((format!("{} case", input.trim()), ll::case_state(alg)));
+        } else {
+            match parse_moves(&input) {
+                Ok(v) => {
+                    let mut state = CubeState::solved();
+                    state.apply_all(&v);
+                    tool.entered = Some((moves_to_string(&v), state));
+                }
+                Err(e) => {
+                    tool.input = input;
+                    tool.error = Some(e);
+                }
+            }
+        }
+    }
+
+    /// Attaches the typed reconstruction to the selected solve if it solves
+    /// the solve's scramble, and saves it with the times.
+    fn submit_recon(&mut self) {
+        let result = self.check_recon();
+        match result {
+            Ok((i, recon)) => {
+                self.times.times[i].recon = Some(recon);
+                self.recon.input.clear();
+                self.recon.error = None;
+                // Not being able to save only loses it when quitting fails too
+                let _ = self.write_times();
+            }
+            Err(e) => self.recon.error = Some(e),
+        }
+    }
+
+    fn check_recon(&self) -> Result<(usize, Reconstruction), String> {
+        let i = self.recon_target().ok_or("No solves to reconstruct yet")?;
+        let scramble = match (&self.times.times[i].scramble, self.puzzle.size()) {
+            (_, size) if size != 3 => return Err(format!("Only 3x3 solves can be checked, not {}", self.puzzle)),
+            (Some(v), _) => parse_moves(v).map_err(|e| format!("Bad stored scramble: {}", e))?,
+            (None, _) => return Err("This time was recorded without its scramble".to_string()),
+        };
+        let recon = Reconstruction::parse(&self.recon.input)
+            .map_err(|e: ReconError| format!("Not a reconstruction: {}", e))?;
+        match recon.solves(&scramble) {
+            true => Ok((i, recon)),
+            false => Err("The solution doesn't solve the scramble".to_string()),
+        }
+    }
+
+    /// Turns the virtual cube by the move bound to `key`. The first move
+    /// starts the timer and solving the cube stops it, saving the solve.
+    fn turn(&mut self, key: char) {
+        if self.filter.searching() {
+            return;
+        }
+        let vc = &mut self.virtual_cube;
+        let (state, mv) = match (&mut vc.state, vc.keymap.get(key)) {
+            (Some(state), Some(mv)) => (state, mv),
+            _ => return,
+        };
+        if !self.timer.on {
+            self.timer.space_press();
+            self.tick_rate = Duration::from_millis(100);
+        }
+        state.apply(mv);
+        vc.moves.push(mv);
+        if !state.is_solved() {
+            return;
+        }
+        let mut time = match self.timer.space_press() {
+            Some(v) => v,
+            None => return,
+        };
+        self.tick_rate = Duration::from_millis(1000);
+        let scramble = match &self.scramble {
+            Scramble::Cube(v) => v.clone(),
+            _ => return,
+        };
+        let solve = Solve {
+            time: time.time,
+            scramble,
+            moves: std::mem::take(&mut vc.moves),
+        };
+        // Not being able to save only loses the moves, the time is kept
+        let _ = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.solves_path())
+            .and_then(|mut v| writeln!(v, "{}", solve));
+        time.recon = Some(Reconstruction::single(solve.moves.clone()));
+        self.virtual_cube.solves.push(solve);
+        self.record(time);
+        self.next_scramble();
+    }
+
+    /// Takes the virtual cube back to the scramble and stops the timer.
+    fn restart_virtual(&mut self) {
+        if self.virtual_cube.moves.is_empty() {
+            return;
+        }
+        self.timer.reset();
+        self.tick_rate = Duration::from_millis(1000);
+        self.virtual_cube.state = self.state.as_ref().and_then(|v| v.cube()).copied();
+        self.virtual_cube.moves.clear();
+    }
+
+    /// Enter starts typing key bindings, and again adds the typed ones to
+    /// the key map and saves it.
+    fn submit_keymap(&mut self) {
+        let vc = &mut self.virtual_cube;
+        if !vc.editing {
+            vc.editing = true;
+            return;
+        }
+        match vc.keymap.with(&vc.input) {
+            Ok(v) => {
+                // Not being able to save only costs the bindings next time
+                let _ = fs::write(self.path.with_file_name("keys"), v.to_string());
+                vc.keymap = v;
+                vc.input.clear();
+                vc.error = None;
+                vc.editing = false;
+            }
+            Err(e) => vc.error = Some(e),
+        }
+    }
+
+    /// Adds the typed pattern, shows it and saves it for next time. A
+    /// pattern that doesn't read stays typed in to be fixed.
+    fn submit_pattern(&mut self) {
+        let patterns = &mut self.patterns;
+        match Pattern::parse(&patterns.input, &patterns.next_name()) {
+            Ok(v) => {
+                let line = format!("{}: {}\n", v.name, moves_to_string(&v.moves));
+                patterns.list.push(v);
+                patterns.state.select(Some(patterns.list.len() - 1));
+                patterns.input.clear();
+                patterns.error = None;
+                // Not being able to save only loses the pattern next time
+                let path = self.path.with_file_name("patterns");
+                let _ = fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut v| v.write_all(line.as_bytes()));
+            }
+            Err(e) => patterns.error = Some(e),
+        }
+    }
+
+    /// f turns the filter on and off, c and b the cross and block rules,
+    /// and digits make up a new minimum length.
+    fn filter_key(&mut self, c: char) {
+        let filter = &mut self.filter;
+        match c {
+            'f' => filter.enabled = !filter.enabled,
+            'c' => filter.filter.cross = !filter.filter.cross,
+            'b' => filter.filter.block = !filter.filter.block,
+            c if c.is_ascii_digit() => filter.input.push(c),
+            _ => (),
+        }
+    }
+
+    /// Sets the minimum optimal length to the typed number, as far as the
+    /// solver can check it.
+    fn submit_filter(&mut self) {
+        let input = std::mem::take(&mut self.filter.input);
+        if let Ok(v) = input.parse::<usize>() {
+            self.filter.filter.min_length = v.min(MAX_MIN_LENGTH);
+        }
+    }
+
+    /// Starts the hour of an FMC attempt on the current scramble and opens
+    /// the FMC tool to type the solution in.
+    pub fn start_fmc(&mut self) {
+        if self.fmc.started.is_some() {
+            return;
+        }
+        self.fmc = Fmc {
+            started: Some(Instant::now()),
+            ..Fmc::default()
+        };
+        if let Some(i) = self.tools.iter().position(|v| matches!(v, Tool::Fmc)) {
+            self.tools_state.select(Some(i));
+            self.active_tool = Tool::Fmc;
+        }
+        self.route.selected_block = ActiveBlock::Main;
+        self.route.active_block = ActiveBlock::Main;
+    }
+
+    /// Checks the typed FMC solution and records its move count if it
+    /// solves the scramble, or gives up with a DNF if `DNF` was typed. A
+    /// rejected solution stays typed in to be fixed.
+    fn submit_fmc(&mut self) {
+        if self.fmc.started.is_none() {
+            self.fmc.error = Some("Press esc, then space to start an attempt".to_string());
+            return;
+        }
+        let scramble = match &self.scramble {
+            Scramble::Cube(v) => v,
+            _ => return,
+        };
+        if self.fmc.input.trim().eq_ignore_ascii_case("DNF") {
+            self.fmc_dnf("Gave up");
+            return;
+        }
+        match fmc::check(scramble, &self.fmc.input) {
+            Ok(moves) => {
+                let mut time = Time::from(Metric::Obtm.count(&moves) as f64);
+                time.recon = Some(Reconstruction::single(moves.clone()));
+                self.record(time);
+                self.fmc = Fmc {
+                    solution: Some(moves),
+                    ..Fmc::default()
+                };
+                self.next_scramble();
+            }
+            Err(e) => self.fmc.error = Some(format!("Not accepted: {}", e)),
+        }
+    }
+
+    /// Ends the running FMC attempt without a solution, recording a DNF.
+    fn fmc_dnf(&mut self, reason: &str) {
+        self.record(Time::from(DNF));
+        self.fmc = Fmc {
+            error: Some(format!("{}, the attempt is recorded as a DNF", reason)),
+            ..Fmc::default()
+        };
+        self.next_scramble();
+    }
+
+    /// Switches to the typed lettering scheme, or back to Speffz if nothing
+    /// was typed, and saves it for next time.
+    fn submit_lettering(&mut self) {
+        let input = std::mem::take(&mut self.memo_tool.input);
+        let lettering = match input.trim().is_empty() {
+            true => Ok(Lettering::default()),
+            false => Lettering::parse(&input),
+        };
+        match lettering {
+            Ok(v) => {
+                self.memo_tool.lettering = v;
+                self.memo_tool.error = None;
+                // Not being able to save only costs the scheme next time
+                let _ = fs::write(self.path.with_file_name("lettering"), v.to_string());
+            }
+            Err(e) => self.memo_tool.error = Some(e),
+        }
+    }
+
+    /// hjkl move the painter's cursor, color letters paint the sticker under
+    /// it, x goes back to showing the scramble and v switches between the net
+    /// and the 3D views. Painting only works on the net, where the cursor is.
+    /// On Square-1, keys type a scramble instead.
+    fn paint_key(&mut self, c: char) {
+        match c {
+            _ if self.puzzle == Puzzle::Square1 => self.painter.input.push(c),
+            'v' => self.painter.view = self.painter.view.next(),
+            _ if self.painter.view != CubeView::Net => {}
+            'h' => self.painter.mv(Dir::Left),
+            'j' => self.painter.mv(Dir::Down),
+            'k' => self.painter.mv(Dir::Up),
+            'l' => self.painter.mv(Dir::Right),
+            'x' => self.painter = Painter {
+                cursor: self.painter.cursor,
+                view: self.painter.view,
+                ..Painter::default()
+            },
+            c => {
+                if let Some(face) = paint_color(c) {
+                    let scrambled = self
+                        .state
+                        .as_ref()
+                        .and_then(|v| v.cube())
+                        .copied()
+                        .unwrap_or_else(CubeState::solved);
+                    self.painter.paint(face, scrambled);
+                }
+            }
+        }
+    }
+
+    /// Checks the painted cube and hands it to the Solver tool if it is
+    /// solvable.
+    fn submit_painting(&mut self) {
+        if self.puzzle == Puzzle::Square1 {
+            self.submit_square1();
+            return;
+        }
+        let state = match self.painter.state {
+            Some(v) => v,
+            None => return,
+        };
+        match validate(&state) {
+            Ok(_) => {
+                self.painter.checked = Some(Ok(()));
+                self.solver.custom = Some(state.with_centers_fixed());
+                self.solver.entered = None;
+                self.solver.error = None;
+                self.solver.pending = None;
+                self.solver.solution = None;
+            }
+            Err(e) => self.painter.checked = Some(Err(e)),
+        }
+    }
+
+    /// Shows the Square-1 the typed scramble leads to. Nothing typed goes
+    /// back to the scramble.
+    fn submit_square1(&mut self) {
+        let input = std::mem::take(&mut self.painter.input);
+        self.painter.error = None;
+        self.painter.square1 = None;
+        if input.trim().is_empty() {
+            return;
+        }
+        match square1::parse_moves(&input) {
+            Ok(moves) => {
+                let mut state = Square1::solved();
+                for mv in moves {
+                    state.apply(mv);
+                }
+                self.painter.square1 = Some(state);
+                self.playback = None;
+            }
+            Err(e) => self.painter.error = Some(e),
+        }
+    }
+
+    /// Solves the typed facelet string or scramble, or the current scramble
+    /// again if nothing was typed.
+    fn submit_solver(&mut self) {
+        let input = std::mem::take(&mut self.solver.input);
+        self.solver.error = None;
+        self.solver.pending = None;
+        self.solver.entered = None;
+        if input.trim().is_empty() {
+            self.solver.custom = None;
+        } else if looks_like_facelets(&input) {
+            let state = CubeState::from_facelet_string(&input)
+                .and_then(|v| validate(&v).map(|_| v.with_centers_fixed()));
+            match state {
+                Ok(v) => self.solver.custom = Some(v),
+                Err(e) => {
+                    self.solver.error = Some(format!("Not a valid facelet string: {}", e));
+                    return;
+                }
+            }
+        } else {
+            match parse_moves(&input) {
+                Ok(moves) => {
+                    let mut state = CubeState::solved();
+                    state.apply_all(&moves);
+                    self.solver.custom = Some(state.with_centers_fixed());
+                    self.solver.entered = Some(simplify(&moves));
+                }
+                Err(e) => {
+                    self.solver.error = Some(format!("Not a facelet string or scramble: {}", e));
+                    return;
+                }
+            }
+        }
+        self.solver.solution = None;
+        self.update_solver();
+    }
+
+    pub fn on_tick(&mut self) {
+        self.solver.poll();
+        if let Some(v) = self.filter.poll() {
+            // Not being able to save only loses the counts
+            let _ = fs::write(self.path.with_file_name("filter"), self.filter.stats.to_string());
+            if !self.timer.on {
+                self.tick_rate = Duration::from_millis(1000);
+            }
+            self.show_scramble(v);
+        }
+        if self.fmc.remaining() == Some(Duration::ZERO) {
+            self.fmc_dnf("Time's up");
+        }
     }
 }
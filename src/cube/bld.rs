@@ -0,0 +1,392 @@
+//! Blindfolded solving: scrambles that leave the cube in a random
+//! orientation, and tracing a cube into the letter pairs a blindfolded
+//! solver memorizes.
+//!
+//! Pieces are solved one sticker at a time from a buffer, UFR for corners
+//! and UF for edges. Each sticker is named by a letter of a lettering
+//! scheme, Speffz unless another one is set.
+
+use super::cubie::{CubieCube, CORNER_FACELETS, EDGE_FACELETS};
+use super::{scramble_to, CubeState, Face, Layers, Move, FACELET_ORDER};
+use rand::{seq::SliceRandom, Rng};
+use std::fmt::{self, Formatter};
+
+/// Corner and edge positions of the buffers, in `CubieCube` order.
+const CORNER_BUFFER: usize = 0;
+const EDGE_BUFFER: usize = 1;
+
+/// Faces in lettering order, and the corner and edge stickers of a face in
+/// the order they are lettered: clockwise from the top left corner and from
+/// the top edge, seen the way `CubeState` lays the face out.
+const LETTER_FACES: [Face; 6] = [Face::U, Face::L, Face::F, Face::R, Face::B, Face::D];
+const CORNER_STICKERS: [usize; 4] = [0, 2, 8, 6];
+const EDGE_STICKERS: [usize; 4] = [1, 5, 7, 3];
+
+const SPEFFZ: &str = "ABCDEFGHIJKLMNOPQRSTUVWX";
+
+fn rotation(face: Face, prime: bool, half_turn: bool) -> Move {
+    Move {
+        face,
+        layers: Layers::Rotation,
+        prime,
+        half_turn,
+    }
+}
+
+fn wide(face: Face, prime: bool, half_turn: bool) -> Move {
+    Move {
+        face,
+        layers: Layers::Wide,
+        ..rotation(face, prime, half_turn)
+    }
+}
+
+/// A random state scramble followed by wide turns leaving any of the 24
+/// orientations equally likely, as on WCA blindfolded scramble sheets.
+pub fn gen_scramble<R: Rng + ?Sized>(rng: &mut R) -> Vec<Move> {
+    let mut moves = scramble_to(&CubieCube::random(rng));
+    let first = [
+        None,
+        Some(wide(Face::R, false, false)),
+        Some(wide(Face::R, false, true)),
+        Some(wide(Face::R, true, false)),
+        Some(wide(Face::F, false, false)),
+        Some(wide(Face::F, true, false)),
+    ];
+    let second = [
+        None,
+        Some(wide(Face::U, false, false)),
+        Some(wide(Face::U, false, true)),
+        Some(wide(Face::U, true, false)),
+    ];
+    moves.extend(first.choose(rng).unwrap());
+    moves.extend(second.choose(rng).unwrap());
+    moves
+}
+
+/// The rotation turning the cube so that every center is on its own face,
+/// the way it is held for memorizing, and the cube after it.
+fn hold_upright(state: &CubeState) -> (Vec<Move>, CubeState) {
+    let first = [
+        None,
+        Some(rotation(Face::R, false, false)),
+        Some(rotation(Face::R, false, true)),
+        Some(rotation(Face::R, true, false)),
+        Some(rotation(Face::F, false, false)),
+        Some(rotation(Face::F, true, false)),
+    ];
+    let second = [
+        None,
+        Some(rotation(Face::U, false, false)),
+        Some(rotation(Face::U, false, true)),
+        Some(rotation(Face::U, true, false)),
+    ];
+    for a in first {
+        for b in second {
+            let moves: Vec<Move> = a.into_iter().chain(b).collect();
+            let mut held = *state;
+            held.apply_all(&moves);
+            if held.face(Face::U)[4] == Face::U && held.face(Face::F)[4] == Face::F {
+                return (moves, held);
+            }
+        }
+    }
+    unreachable!("one of the 24 orientations puts U on top and F in front")
+}
+
+/// Letters for the 24 corner stickers and the 24 edge stickers, each in
+/// Speffz order: U L F R B D, clockwise on each face.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Lettering {
+    pub corners: [char; 24],
+    pub edges: [char; 24],
+}
+
+/// Why a lettering scheme could not be read.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LetteringError {
+    /// This many letters instead of 24 or 48.
+    Length(usize),
+    /// A letter used for two corner stickers or two edge stickers.
+    Duplicate(char),
+}
+
+impl std::fmt::Display for LetteringError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            LetteringError::Length(n) => write!(f, "{} letters instead of 24 or 48", n)?,
+            LetteringError::Duplicate(c) => write!(f, "`{}` names two stickers", c)?,
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LetteringError {}
+
+impl Default for Lettering {
+    fn default() -> Self {
+        Self::parse(SPEFFZ).expect("Speffz is a valid lettering")
+    }
+}
+
+impl std::fmt::Display for Lettering {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let corners: String = self.corners.iter().collect();
+        let edges: String = self.edges.iter().collect();
+        match corners == edges {
+            true => write!(f, "{}", corners)?,
+            false => write!(f, "{} {}", corners, edges)?,
+        }
+        Ok(())
+    }
+}
+
+impl Lettering {
+    /// Reads 24 letters used for both corners and edges, or 48 letters for
+    /// the corners and then the edges. Whitespace is ignored.
+    pub fn parse(s: &str) -> Result<Self, LetteringError> {
+        let letters: Vec<char> = s.chars().filter(|v| !v.is_whitespace()).collect();
+        let (corners, edges) = match letters.len() {
+            24 => (&letters[..], &letters[..]),
+            48 => letters.split_at(24),
+            n => return Err(LetteringError::Length(n)),
+        };
+        for set in [corners, edges] {
+            for (i, c) in set.iter().enumerate() {
+                if set[..i].contains(c) {
+                    return Err(LetteringError::Duplicate(*c));
+                }
+            }
+        }
+        Ok(Self {
+            corners: corners.try_into().unwrap(),
+            edges: edges.try_into().unwrap(),
+        })
+    }
+
+    pub fn is_speffz(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Lettering order number of the sticker on `facelet`, given the stickers
+/// of the piece type in lettering order.
+fn sticker_number(facelet: usize, stickers: &[usize; 4]) -> usize {
+    let face = FACELET_ORDER[facelet / 9];
+    let i = LETTER_FACES.iter().position(|v| *v == face).unwrap();
+    let k = stickers.iter().position(|v| *v == facelet % 9).unwrap();
+    i * 4 + k
+}
+
+/// The memo of one piece type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Trace {
+    /// Stickers to shoot the buffer to, as lettering order numbers, split
+    /// into cycles. Every cycle but the first starts with a cycle break.
+    pub cycles: Vec<Vec<usize>>,
+    /// Pieces in their own position but twisted or flipped, as a position
+    /// and the clockwise twist or flip.
+    pub twisted: Vec<(usize, u8)>,
+}
+
+impl Trace {
+    pub fn targets(&self) -> usize {
+        self.cycles.iter().map(|v| v.len()).sum()
+    }
+
+    /// The targets written with `letters`, in pairs.
+    pub fn memo(&self, letters: &[char; 24]) -> String {
+        let targets: Vec<char> = self.cycles.concat().iter().map(|v| letters[*v]).collect();
+        targets
+            .chunks(2)
+            .map(|v| v.iter().collect())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+/// Corner and edge memo of a cube held upright.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Memo {
+    /// Rotation from the scrambled orientation to upright.
+    pub rotation: Vec<Move>,
+    pub corners: Trace,
+    pub edges: Trace,
+}
+
+impl Memo {
+    /// Whether there is an odd number of targets, leaving UF and UR
+    /// swapped after the corners.
+    pub fn parity(&self) -> bool {
+        self.corners.targets() % 2 == 1
+    }
+}
+
+/// Traces the pieces of one type starting from `buffer`. `home(p, k)` is
+/// where the sticker on the `k`th facelet of position `p` belongs, as a
+/// position and facelet, and `number(p, k)` the lettering order number of
+/// that facelet.
+fn trace_pieces(
+    positions: usize,
+    buffer: usize,
+    home: impl Fn(usize, usize) -> (usize, usize),
+    number: impl Fn(usize, usize) -> usize,
+) -> Trace {
+    let mut done = vec![false; positions];
+    let mut twisted = vec![];
+    for (p, v) in done.iter_mut().enumerate() {
+        let (to, k) = home(p, 0);
+        if to == p {
+            *v = true;
+            if k != 0 && p != buffer {
+                twisted.push((p, k as u8));
+            }
+        }
+    }
+    done[buffer] = true;
+    let mut cycles = vec![];
+    let mut cycle = vec![];
+    let (mut start, mut at) = (buffer, (buffer, 0));
+    loop {
+        let target = home(at.0, at.1);
+        if target.0 != start {
+            done[target.0] = true;
+            cycle.push(number(target.0, target.1));
+            at = target;
+            continue;
+        }
+        // Back at the piece the cycle started from
+        if start != buffer {
+            cycle.push(number(target.0, target.1));
+        }
+        if !cycle.is_empty() {
+            cycles.push(std::mem::take(&mut cycle));
+        }
+        match (0..positions)
+            .filter(|v| !done[*v])
+            .min_by_key(|v| number(*v, 0))
+        {
+            Some(p) => {
+                done[p] = true;
+                cycle.push(number(p, 0));
+                (start, at) = (p, (p, 0));
+            }
+            None => break,
+        }
+    }
+    Trace { cycles, twisted }
+}
+
+/// The memo of a cube, held with U on top and F in front, or `None` if the
+/// stickers don't make up a real cube.
+pub fn trace(state: &CubeState) -> Option<Memo> {
+    let (rotation, held) = hold_upright(state);
+    let cube = CubieCube::from_state(&held)?;
+    let corners = trace_pieces(
+        8,
+        CORNER_BUFFER,
+        |p, k| {
+            let piece = cube.cp[p] as usize;
+            (piece, (k + 3 - cube.co[p] as usize) % 3)
+        },
+        |p, k| sticker_number(CORNER_FACELETS[p][k], &CORNER_STICKERS),
+    );
+    let edges = trace_pieces(
+        12,
+        EDGE_BUFFER,
+        |p, k| (cube.ep[p] as usize, (k + cube.eo[p] as usize) % 2),
+        |p, k| sticker_number(EDGE_FACELETS[p][k], &EDGE_STICKERS),
+    );
+    Some(Memo {
+        rotation,
+        corners,
+        edges,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::notation::parse_moves;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn memo(scramble: &str) -> (String, String) {
+        let mut state = CubeState::solved();
+        state.apply_all(&parse_moves(scramble).unwrap());
+        let memo = trace(&state).unwrap();
+        let letters = Lettering::default();
+        (
+            memo.corners.memo(&letters.corners),
+            memo.edges.memo(&letters.edges),
+        )
+    }
+
+    #[test]
+    fn letters_stickers_in_speffz_order() {
+        assert_eq!(sticker_number(CORNER_FACELETS[2][0], &CORNER_STICKERS), 0);
+        assert_eq!(sticker_number(CORNER_FACELETS[0][0], &CORNER_STICKERS), 2);
+        assert_eq!(sticker_number(CORNER_FACELETS[5][1], &CORNER_STICKERS), 6);
+        assert_eq!(sticker_number(EDGE_FACELETS[1][0], &EDGE_STICKERS), 2);
+        assert_eq!(sticker_number(EDGE_FACELETS[1][1], &EDGE_STICKERS), 8);
+        assert_eq!(sticker_number(EDGE_FACELETS[4][0], &EDGE_STICKERS), 21);
+    }
+
+    #[test]
+    fn traces_simple_cycles() {
+        assert_eq!(memo(""), (String::new(), String::new()));
+        // U brings UR to UF, UB to UR and UL to UB
+        assert_eq!(memo("U").1, "BA D");
+        // U2 swaps UF with UB, then UR with UL after a cycle break
+        assert_eq!(memo("U2"), ("AB DB".to_string(), "AB DB".to_string()));
+        // R is a four-cycle of corners including the buffer
+        let mut state = CubeState::solved();
+        state.apply_all(&parse_moves("R").unwrap());
+        let memo = trace(&state).unwrap();
+        assert!(memo.parity());
+        assert_eq!(memo.corners.targets(), 3);
+    }
+
+    #[test]
+    fn finds_twists_and_flips() {
+        // Superflip: every edge flips in place, the buffer's flip is implied
+        let mut state = CubeState::solved();
+        let superflip = "U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2";
+        state.apply_all(&parse_moves(superflip).unwrap());
+        let memo = trace(&state).unwrap();
+        assert_eq!(memo.edges.targets(), 0);
+        assert_eq!(memo.edges.twisted.len(), 11);
+        // Twists the buffer one way and the corner brought under it the other
+        let mut state = CubeState::solved();
+        let insert = "R' D' R D ";
+        let twist = format!("{} U {} U'", insert.repeat(2), insert.repeat(4));
+        state.apply_all(&parse_moves(&twist).unwrap());
+        let memo = trace(&state).unwrap();
+        assert_eq!(memo.corners.targets(), 0);
+        assert_eq!(memo.corners.twisted.len(), 1);
+    }
+
+    #[test]
+    fn orientation_does_not_change_the_memo() {
+        let moves = gen_scramble(&mut StdRng::seed_from_u64(1));
+        let mut state = CubeState::solved();
+        state.apply_all(&moves);
+        let mut upright = state;
+        upright.apply_all(&hold_upright(&state).0);
+        let (memo, upright) = (trace(&state).unwrap(), trace(&upright).unwrap());
+        assert!(upright.rotation.is_empty());
+        assert_eq!(memo.corners.targets() % 2, memo.edges.targets() % 2);
+        assert_eq!((memo.corners, memo.edges), (upright.corners, upright.edges));
+    }
+
+    #[test]
+    fn reads_letterings() {
+        assert!(Lettering::default().is_speffz());
+        let custom = Lettering::parse(&format!("{} {}", SPEFFZ, SPEFFZ.to_lowercase())).unwrap();
+        assert_eq!(custom.edges[0], 'a');
+        assert_eq!(Lettering::parse("ABC"), Err(LetteringError::Length(3)));
+        assert_eq!(
+            Lettering::parse("AACDEFGHIJKLMNOPQRSTUVWX"),
+            Err(LetteringError::Duplicate('A'))
+        );
+    }
+}
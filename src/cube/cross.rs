@@ -0,0 +1,188 @@
+//! Optimal crosses. The four D layer edges are tracked on their own and a
+//! breadth-first search over every way they can be placed gives the exact
+//! number of moves left to solve them, which a search then just follows
+//! downhill. Crosses of other colors are solved by first rotating that color
+//! to the bottom.
+
+use super::cubie::{index_move, move_cube, CubieCube};
+use super::{CubeState, Face, Layers, Move, FACELET_ORDER};
+use std::{collections::VecDeque, sync::OnceLock};
+
+/// The cross edges DR, DF, DL and DB, as positions in `CubieCube::ep`.
+const CROSS_EDGES: [usize; 4] = [4, 5, 6, 7];
+
+/// Each cross edge is one of 24 position and orientation pairs.
+const N_CROSS: usize = 24 * 24 * 24 * 24;
+
+/// Optimal solution to the cross of one color, starting from the scramble
+/// orientation.
+#[derive(Clone, Debug)]
+pub struct CrossSolution {
+    pub color: Face,
+    /// Rotation bringing `color` to the bottom, if it isn't there already.
+    pub rotation: Option<Move>,
+    pub moves: Vec<Move>,
+}
+
+/// For each of the 24 position and orientation pairs of a single edge, the
+/// pair it moves to under each of the 18 face turns.
+fn edge_moves() -> &'static [[u8; 18]; 24] {
+    static TABLE: OnceLock<[[u8; 18]; 24]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [[0; 18]; 24];
+        for (i, row) in table.iter_mut().enumerate() {
+            let (from, ori) = (i / 2, i % 2);
+            for (m, v) in row.iter_mut().enumerate() {
+                let mv = move_cube(m);
+                let to = mv.ep.iter().position(|p| *p as usize == from).unwrap();
+                *v = (to * 2 + (ori + mv.eo[to] as usize) % 2) as u8;
+            }
+        }
+        table
+    })
+}
+
+fn encode(edges: [u8; 4]) -> usize {
+    edges.iter().fold(0, |acc, v| acc * 24 + *v as usize)
+}
+
+fn apply(edges: [u8; 4], m: usize) -> [u8; 4] {
+    edges.map(|v| edge_moves()[v as usize][m])
+}
+
+/// Moves needed to solve the D cross from every placement of its edges.
+fn distances() -> &'static [u8] {
+    static TABLE: OnceLock<Vec<u8>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let solved = CROSS_EDGES.map(|v| (v * 2) as u8);
+        let mut table = vec![u8::MAX; N_CROSS];
+        let mut queue = VecDeque::new();
+        table[encode(solved)] = 0;
+        queue.push_back(solved);
+        while let Some(edges) = queue.pop_front() {
+            let d = table[encode(edges)];
+            for m in 0..18 {
+                let next = apply(edges, m);
+                if table[encode(next)] == u8::MAX {
+                    table[encode(next)] = d + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+        table
+    })
+}
+
+/// An optimal solution to the D cross of a cube.
+pub fn solve_cross(cube: &CubieCube) -> Vec<Move> {
+    let table = distances();
+    let mut edges = CROSS_EDGES.map(|piece| {
+        let pos = cube.ep.iter().position(|v| *v as usize == piece).unwrap();
+        (pos * 2 + cube.eo[pos] as usize) as u8
+    });
+    let mut moves = vec![];
+    while table[encode(edges)] > 0 {
+        let d = table[encode(edges)];
+        let m = (0..18)
+            .find(|m| table[encode(apply(edges, *m))] < d)
+            .expect("some move gets closer to the cross");
+        edges = apply(edges, m);
+        moves.push(index_move(m));
+    }
+    moves
+}
+
+/// The rotation taking the center on `face` to D.
+fn rotation_to_bottom(face: Face) -> Option<Move> {
+    let (face, prime, half_turn) = match face {
+        Face::D | Face::None => return None,
+        Face::U => (Face::R, false, true),
+        Face::F => (Face::R, true, false),
+        Face::B => (Face::R, false, false),
+        Face::R => (Face::F, false, false),
+        Face::L => (Face::F, true, false),
+    };
+    Some(Move {
+        face,
+        layers: Layers::Rotation,
+        prime,
+        half_turn,
+    })
+}
+
+/// Optimal crosses of all six colors of a cube held however the scramble
+/// left it, or `None` if the stickers don't make up a real cube.
+pub fn solve_crosses(state: &CubeState) -> Option<Vec<CrossSolution>> {
+    [Face::D, Face::U, Face::F, Face::B, Face::R, Face::L]
+        .iter()
+        .map(|color| {
+            // Wide moves at the end of a scramble take centers elsewhere
+            let at = FACELET_ORDER
+                .into_iter()
+                .find(|v| state.face(*v)[4] == *color)?;
+            let rotation = rotation_to_bottom(at);
+            let mut rotated = *state;
+            if let Some(v) = rotation {
+                rotated.apply(v);
+            }
+            let cube = CubieCube::from_state(&rotated.with_centers_fixed())?;
+            Some(CrossSolution {
+                color: *color,
+                rotation,
+                moves: solve_cross(&cube),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::notation::parse_moves;
+
+    fn cross_solved(state: &CubeState, color: Face) -> bool {
+        let cube = CubieCube::from_state(&state.with_centers_fixed()).unwrap();
+        state.face(Face::D)[4] == color
+            && CROSS_EDGES
+                .iter()
+                .all(|v| cube.ep[*v] as usize == *v && cube.eo[*v] == 0)
+    }
+
+    #[test]
+    fn solved_cube_needs_no_moves() {
+        for cross in solve_crosses(&CubeState::solved()).unwrap() {
+            assert!(cross.moves.is_empty(), "{}", cross.color);
+        }
+    }
+
+    #[test]
+    fn undoes_short_scrambles_at_most_as_long() {
+        let moves = parse_moves("F R' D2").unwrap();
+        let mut state = CubeState::solved();
+        state.apply_all(&moves);
+        let crosses = solve_crosses(&state).unwrap();
+        assert_eq!(crosses[0].moves.len(), 3);
+        assert!(crosses.iter().all(|v| v.moves.len() <= 3));
+    }
+
+    #[test]
+    fn solutions_solve_their_cross() {
+        // The second ends in wide moves, like a 3BLD scramble
+        for scramble in [
+            "R U2 F' L D B2 R' U F2 D' L2 B U' R2 F",
+            "R U2 F' L D B2 R' U F2 D' L2 B U' R2 F Rw Uw'",
+        ] {
+            let mut state = CubeState::solved();
+            state.apply_all(&parse_moves(scramble).unwrap());
+            for cross in solve_crosses(&state).unwrap() {
+                assert!(cross.moves.len() <= 8);
+                let mut solved = state;
+                if let Some(v) = cross.rotation {
+                    solved.apply(v);
+                }
+                solved.apply_all(&cross.moves);
+                assert!(cross_solved(&solved, cross.color), "{}", cross.color);
+            }
+        }
+    }
+}
@@ -0,0 +1,439 @@
+//! The 2x2 cube. Holding the DBL corner in place, U, R and F turns are all
+//! it takes, and the other seven corners can be arranged 7! 3^6 = 3,674,160
+//! ways: few enough for a breadth-first search to find the exact number of
+//! moves every one of them is from solved. Solutions then just follow those
+//! distances downhill.
+
+use super::cubie::{corner_at, move_cube, perm_rank, perm_unrank, CORNER_COLORS, CORNER_FACELETS};
+use super::nxn::{NxnMove, NxnState};
+use super::{CubeState, Face, FACELET_ORDER};
+use rand::Rng;
+use std::{collections::VecDeque, sync::OnceLock};
+
+const N_PERM: usize = 5040;
+const N_TWIST: usize = 729;
+pub const N_STATES: usize = N_PERM * N_TWIST;
+
+/// U, R and F turns, three powers each, in the order of the 3x3 move
+/// tables.
+const N_MOVES: usize = 9;
+
+/// Corner positions other than DBL, which never moves.
+const FREE_CORNERS: [usize; 7] = [0, 1, 2, 3, 4, 5, 7];
+const DBL: usize = 6;
+
+/// Random states closer to solved than this are drawn again, as in WCA
+/// scrambles.
+pub const MIN_SCRAMBLE_LENGTH: usize = 4;
+
+/// A 2x2 cube as the piece and twist in each corner position, in the
+/// orders of `CORNER_FACELETS`. The DBL corner is always solved.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Cube2 {
+    pub cp: [u8; 8],
+    pub co: [u8; 8],
+}
+
+/// What to look for in inspection: an optimal solution and the optimal
+/// first layers.
+#[derive(Clone, Debug)]
+pub struct Inspection {
+    pub solution: Vec<NxnMove>,
+    pub layers: Vec<LayerSolution>,
+}
+
+/// Optimal way to build the first layer of one color, on whichever face it
+/// ends up.
+#[derive(Clone, Debug)]
+pub struct LayerSolution {
+    pub color: Face,
+    /// Face the layer is on once built.
+    pub face: Face,
+    pub moves: Vec<NxnMove>,
+}
+
+impl Cube2 {
+    pub fn solved() -> Self {
+        Self {
+            cp: [0, 1, 2, 3, 4, 5, 6, 7],
+            co: [0; 8],
+        }
+    }
+
+    /// Reads the corners off a 2x2. Returns `None` if the stickers don't
+    /// make up real corners that a 2x2 can be turned to, or if DBL isn't
+    /// solved, which scrambles never move.
+    pub fn from_state(state: &NxnState) -> Option<Self> {
+        if state.n != 2 {
+            return None;
+        }
+        let mut cube = CubeState::solved();
+        for facelets in CORNER_FACELETS {
+            for i in facelets {
+                cube.facelets[i] = state.facelets[small_facelet(i)];
+            }
+        }
+        let mut result = Self::solved();
+        for i in 0..8 {
+            (result.cp[i], result.co[i]) = corner_at(&cube, i)?;
+        }
+        let mut pieces = result.cp;
+        pieces.sort();
+        let twisted = result.co.iter().map(|v| *v as usize).sum::<usize>() % 3 != 0;
+        let dbl_solved = result.cp[DBL] as usize == DBL && result.co[DBL] == 0;
+        match pieces == Self::solved().cp && !twisted && dbl_solved {
+            true => Some(result),
+            false => None,
+        }
+    }
+
+    /// Color of sticker `k` of the corner in position `i`, counting
+    /// clockwise from the U or D sticker.
+    fn color(&self, i: usize, k: usize) -> Face {
+        CORNER_COLORS[self.cp[i] as usize][(k + 3 - self.co[i] as usize) % 3]
+    }
+
+    /// Applies move `m` of the move tables.
+    fn turn(&mut self, m: usize) {
+        let mv = move_cube(m);
+        let (cp, co) = (self.cp, self.co);
+        for i in 0..8 {
+            let from = mv.cp[i] as usize;
+            self.cp[i] = cp[from];
+            self.co[i] = (co[from] + mv.co[i]) % 3;
+        }
+    }
+
+    fn perm(&self) -> usize {
+        // DBL's piece number is skipped over, leaving 0..7
+        let p = FREE_CORNERS.map(|i| self.cp[i] - (self.cp[i] > DBL as u8) as u8);
+        perm_rank(&p)
+    }
+
+    fn set_perm(&mut self, rank: usize) {
+        let mut p = [0; 7];
+        perm_unrank(rank, &mut p);
+        for (i, v) in FREE_CORNERS.iter().zip(p) {
+            self.cp[*i] = v + (v >= DBL as u8) as u8;
+        }
+    }
+
+    fn twist(&self) -> usize {
+        FREE_CORNERS[..6]
+            .iter()
+            .fold(0, |acc, i| acc * 3 + self.co[*i] as usize)
+    }
+
+    fn set_twist(&mut self, mut twist: usize) {
+        let mut sum = 0;
+        for i in FREE_CORNERS[..6].iter().rev() {
+            self.co[*i] = (twist % 3) as u8;
+            sum += self.co[*i];
+            twist /= 3;
+        }
+        self.co[FREE_CORNERS[6]] = (3 - sum % 3) % 3;
+    }
+
+    /// Index of the state in the distance table, 0..N_STATES.
+    pub fn index(&self) -> usize {
+        self.perm() * N_TWIST + self.twist()
+    }
+
+    pub fn from_index(index: usize) -> Self {
+        let mut cube = Self::solved();
+        cube.set_perm(index / N_TWIST);
+        cube.set_twist(index % N_TWIST);
+        cube
+    }
+
+    /// Whether the four corners on `face` make up a solved layer, of
+    /// whatever color.
+    fn layer_solved(&self, face: Face) -> bool {
+        let f = FACELET_ORDER.iter().position(|v| *v == face).unwrap_or(0);
+        // Stickers of the layer's four corners, by the face each is on
+        let mut stickers = [[(0, Face::None); 3]; 4];
+        let corners = CORNER_FACELETS
+            .iter()
+            .enumerate()
+            .filter(|(_, facelets)| facelets.iter().any(|v| v / 9 == f));
+        for (corner, (i, facelets)) in stickers.iter_mut().zip(corners) {
+            *corner = [0, 1, 2].map(|k| (facelets[k] / 9, self.color(i, k)));
+        }
+        // Every face the layer touches shows a single color on it
+        (0..6).all(|side| {
+            let mut colors = stickers.iter().flatten().filter(|v| v.0 == side);
+            match colors.next() {
+                Some(first) => colors.all(|v| v.1 == first.1),
+                None => true,
+            }
+        })
+    }
+}
+
+/// Index in a 2x2 `NxnState` of the corner sticker at 3x3 facelet `i`.
+fn small_facelet(i: usize) -> usize {
+    let (face, row, col) = (i / 9, i % 9 / 3, i % 3);
+    face * 4 + row / 2 * 2 + col / 2
+}
+
+fn nxn_move(m: usize) -> NxnMove {
+    NxnMove {
+        face: FACELET_ORDER[m / 3],
+        width: 1,
+        prime: m % 3 == 2,
+        half_turn: m % 3 == 1,
+    }
+}
+
+/// Where each move takes each corner permutation and each twist.
+struct MoveTables {
+    perms: Vec<[u16; N_MOVES]>,
+    twists: Vec<[u16; N_MOVES]>,
+}
+
+fn move_tables() -> &'static MoveTables {
+    static TABLES: OnceLock<MoveTables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let mut perms = vec![[0; N_MOVES]; N_PERM];
+        for (rank, row) in perms.iter_mut().enumerate() {
+            for (m, v) in row.iter_mut().enumerate() {
+                let mut cube = Cube2::solved();
+                cube.set_perm(rank);
+                cube.turn(m);
+                *v = cube.perm() as u16;
+            }
+        }
+        let mut twists = vec![[0; N_MOVES]; N_TWIST];
+        for (twist, row) in twists.iter_mut().enumerate() {
+            for (m, v) in row.iter_mut().enumerate() {
+                let mut cube = Cube2::solved();
+                cube.set_twist(twist);
+                cube.turn(m);
+                *v = cube.twist() as u16;
+            }
+        }
+        MoveTables { perms, twists }
+    })
+}
+
+fn apply(index: usize, m: usize) -> usize {
+    let tables = move_tables();
+    tables.perms[index / N_TWIST][m] as usize * N_TWIST + tables.twists[index % N_TWIST][m] as usize
+}
+
+/// Moves needed to solve every 2x2 state, by index.
+fn distances() -> &'static [u8] {
+    static TABLE: OnceLock<Vec<u8>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = vec![u8::MAX; N_STATES];
+        let mut queue = VecDeque::new();
+        let solved = Cube2::solved().index();
+        table[solved] = 0;
+        queue.push_back(solved as u32);
+        while let Some(index) = queue.pop_front() {
+            let d = table[index as usize];
+            for m in 0..N_MOVES {
+                let next = apply(index as usize, m);
+                if table[next] == u8::MAX {
+                    table[next] = d + 1;
+                    queue.push_back(next as u32);
+                }
+            }
+        }
+        table
+    })
+}
+
+/// An optimal solution, in the half turn metric.
+pub fn solve(cube: &Cube2) -> Vec<NxnMove> {
+    let table = distances();
+    let mut index = cube.index();
+    let mut moves = vec![];
+    while table[index] > 0 {
+        let d = table[index];
+        let m = (0..N_MOVES)
+            .find(|m| table[apply(index, *m)] < d)
+            .expect("some move gets closer to solved");
+        index = apply(index, m);
+        moves.push(nxn_move(m));
+    }
+    moves
+}
+
+/// Optimal first layers of all six colors, shortest first. Layers of the
+/// same length keep the color order of `FACELET_ORDER`.
+pub fn solve_layers(cube: &Cube2) -> Vec<LayerSolution> {
+    let mut found: Vec<Option<LayerSolution>> = vec![None; 6];
+    let mut depth = 0;
+    // Solving the whole cube builds every layer, so this always ends
+    while found.iter().any(Option::is_none) {
+        search_layers(*cube, depth, &mut vec![], &mut found);
+        depth += 1;
+    }
+    let mut layers: Vec<LayerSolution> = found.into_iter().flatten().collect();
+    layers.sort_by_key(|v| v.moves.len());
+    layers
+}
+
+/// Tries every sequence of `depth` more moves that never turns the same
+/// face twice in a row, recording the first layer built of each color.
+fn search_layers(
+    cube: Cube2,
+    depth: usize,
+    moves: &mut Vec<usize>,
+    found: &mut [Option<LayerSolution>],
+) {
+    if depth == 0 {
+        for (i, face) in FACELET_ORDER.iter().enumerate() {
+            if !cube.layer_solved(*face) {
+                continue;
+            }
+            let sticker = CORNER_FACELETS
+                .iter()
+                .enumerate()
+                .find_map(|(c, facelets)| {
+                    let k = facelets.iter().position(|v| v / 9 == i)?;
+                    Some(cube.color(c, k))
+                });
+            let color = sticker.unwrap_or(Face::None);
+            let slot = FACELET_ORDER.iter().position(|v| *v == color).unwrap_or(0);
+            if found[slot].is_none() {
+                found[slot] = Some(LayerSolution {
+                    color,
+                    face: *face,
+                    moves: moves.iter().map(|m| nxn_move(*m)).collect(),
+                });
+            }
+        }
+        return;
+    }
+    for m in 0..N_MOVES {
+        if moves.last().is_some_and(|v| v / 3 == m / 3) {
+            continue;
+        }
+        let mut next = cube;
+        next.turn(m);
+        moves.push(m);
+        search_layers(next, depth - 1, moves, found);
+        moves.pop();
+    }
+}
+
+/// Optimal solution and first layers of a 2x2, or `None` if it isn't one
+/// `Cube2` can read.
+pub fn inspect(state: &NxnState) -> Option<Inspection> {
+    let cube = Cube2::from_state(state)?;
+    Some(Inspection {
+        solution: solve(&cube),
+        layers: solve_layers(&cube),
+    })
+}
+
+/// A random state scramble: a uniformly random 2x2 at least
+/// `MIN_SCRAMBLE_LENGTH` moves from solved, reached by undoing an optimal
+/// solution to it.
+pub fn gen_scramble<R: Rng + ?Sized>(rng: &mut R) -> Vec<NxnMove> {
+    let table = distances();
+    let index = loop {
+        let index = rng.gen_range(0..N_STATES);
+        if table[index] as usize >= MIN_SCRAMBLE_LENGTH {
+            break index;
+        }
+    };
+    solve(&Cube2::from_index(index))
+        .iter()
+        .rev()
+        .map(|v| NxnMove {
+            prime: !v.prime && !v.half_turn,
+            ..*v
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn stickers(cube: &Cube2) -> NxnState {
+        let mut state = NxnState::solved(2);
+        for (i, facelets) in CORNER_FACELETS.iter().enumerate() {
+            for (k, v) in facelets.iter().enumerate() {
+                state.facelets[small_facelet(*v)] = cube.color(i, k);
+            }
+        }
+        state
+    }
+
+    fn state_after(moves: &[NxnMove]) -> NxnState {
+        let mut state = NxnState::solved(2);
+        state.apply_all(moves);
+        state
+    }
+
+    #[test]
+    fn reads_back_states_and_indexes() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let cube = Cube2::from_index(rng.gen_range(0..N_STATES));
+            assert_eq!(Cube2::from_index(cube.index()), cube);
+            assert_eq!(Cube2::from_state(&stickers(&cube)), Some(cube));
+        }
+        let mut painted = NxnState::solved(2);
+        painted.facelets.swap(0, 4);
+        assert_eq!(Cube2::from_state(&painted), None);
+        let turned = state_after(&[NxnMove {
+            face: Face::L,
+            width: 1,
+            prime: false,
+            half_turn: false,
+        }]);
+        assert_eq!(Cube2::from_state(&turned), None);
+    }
+
+    #[test]
+    fn distances_cover_every_state() {
+        let table = distances();
+        assert!(table.iter().all(|v| *v <= 11));
+        assert_eq!(table.iter().filter(|v| **v == 11).count(), 2644);
+    }
+
+    #[test]
+    fn scrambles_are_solved_optimally() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..20 {
+            let scramble = gen_scramble(&mut rng);
+            assert!(scramble.len() >= MIN_SCRAMBLE_LENGTH);
+            let cube = Cube2::from_state(&state_after(&scramble)).unwrap();
+            let solution = solve(&cube);
+            assert_eq!(solution.len(), scramble.len());
+            let mut state = stickers(&cube);
+            state.apply_all(&solution);
+            assert_eq!(state, NxnState::solved(2));
+        }
+    }
+
+    #[test]
+    fn builds_layers_of_every_color() {
+        let moves =
+            [(Face::R, false), (Face::U, true), (Face::F, false)].map(|(face, prime)| NxnMove {
+                face,
+                width: 1,
+                prime,
+                half_turn: false,
+            });
+        let cube = Cube2::from_state(&state_after(&moves)).unwrap();
+        let layers = solve_layers(&cube);
+        assert_eq!(layers.len(), 6);
+        assert!(layers
+            .windows(2)
+            .all(|v| v[0].moves.len() <= v[1].moves.len()));
+        for layer in layers {
+            assert!(layer.moves.len() <= 3);
+            let mut state = stickers(&cube);
+            state.apply_all(&layer.moves);
+            let built = Cube2::from_state(&state).unwrap();
+            assert!(built.layer_solved(layer.face), "{}", layer.color);
+        }
+    }
+}
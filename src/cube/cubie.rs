@@ -0,0 +1,329 @@
+use super::{CubeState, Face, Layers, Move, FACELET_ORDER};
+use rand::{seq::SliceRandom, Rng};
+use std::sync::OnceLock;
+
+/// Facelets of each corner position (URF, UFL, ULB, UBR, DFR, DLF, DBL, DRB),
+/// starting with the U or D sticker and going clockwise.
+pub const CORNER_FACELETS: [[usize; 3]; 8] = [
+    [8, 9, 20],
+    [6, 18, 38],
+    [0, 36, 47],
+    [2, 45, 11],
+    [29, 26, 15],
+    [27, 44, 24],
+    [33, 53, 42],
+    [35, 17, 51],
+];
+
+/// Facelets of each edge position (UR, UF, UL, UB, DR, DF, DL, DB, FR, FL,
+/// BL, BR), starting with the U/D sticker, or the F/B sticker for the
+/// E-slice edges.
+pub const EDGE_FACELETS: [[usize; 2]; 12] = [
+    [5, 10],
+    [7, 19],
+    [3, 37],
+    [1, 46],
+    [32, 16],
+    [28, 25],
+    [30, 43],
+    [34, 52],
+    [23, 12],
+    [21, 41],
+    [50, 39],
+    [48, 14],
+];
+
+pub const CORNER_COLORS: [[Face; 3]; 8] = [
+    [Face::U, Face::R, Face::F],
+    [Face::U, Face::F, Face::L],
+    [Face::U, Face::L, Face::B],
+    [Face::U, Face::B, Face::R],
+    [Face::D, Face::F, Face::R],
+    [Face::D, Face::L, Face::F],
+    [Face::D, Face::B, Face::L],
+    [Face::D, Face::R, Face::B],
+];
+
+pub const EDGE_COLORS: [[Face; 2]; 12] = [
+    [Face::U, Face::R],
+    [Face::U, Face::F],
+    [Face::U, Face::L],
+    [Face::U, Face::B],
+    [Face::D, Face::R],
+    [Face::D, Face::F],
+    [Face::D, Face::L],
+    [Face::D, Face::B],
+    [Face::F, Face::R],
+    [Face::F, Face::L],
+    [Face::B, Face::L],
+    [Face::B, Face::R],
+];
+
+/// Number of E-slice edge positions combinations, C(12, 4).
+pub const N_SLICE: usize = 495;
+
+/// A 3x3 cube described by where each corner and edge piece is and how it is
+/// oriented.
+///
+/// `cp[i]` is the corner piece sitting in corner position `i` and `co[i]` its
+/// clockwise twist, likewise `ep`/`eo` for edges. Pieces and positions use the
+/// orders of `CORNER_FACELETS` and `EDGE_FACELETS`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CubieCube {
+    pub cp: [u8; 8],
+    pub co: [u8; 8],
+    pub ep: [u8; 12],
+    pub eo: [u8; 12],
+}
+
+impl CubieCube {
+    pub fn solved() -> Self {
+        Self {
+            cp: [0, 1, 2, 3, 4, 5, 6, 7],
+            co: [0; 8],
+            ep: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            eo: [0; 12],
+        }
+    }
+
+    /// Reads the pieces off a facelet cube. Returns `None` if some sticker
+    /// combination is not a real piece.
+    pub fn from_state(state: &CubeState) -> Option<Self> {
+        let mut cube = Self::solved();
+        for i in 0..8 {
+            (cube.cp[i], cube.co[i]) = corner_at(state, i)?;
+        }
+        for i in 0..12 {
+            (cube.ep[i], cube.eo[i]) = edge_at(state, i)?;
+        }
+        Some(cube)
+    }
+
+    /// A uniformly random solvable cube.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut cube = Self::solved();
+        cube.cp.shuffle(rng);
+        cube.ep.shuffle(rng);
+        if parity(&cube.cp) != parity(&cube.ep) {
+            cube.ep.swap(10, 11);
+        }
+        cube.set_twist(rng.gen_range(0..2187));
+        cube.set_flip(rng.gen_range(0..2048));
+        cube
+    }
+
+    /// Applies `other` on top of this cube.
+    pub fn multiply(&mut self, other: &CubieCube) {
+        let (cp, co, ep, eo) = (self.cp, self.co, self.ep, self.eo);
+        for i in 0..8 {
+            let from = other.cp[i] as usize;
+            self.cp[i] = cp[from];
+            self.co[i] = (co[from] + other.co[i]) % 3;
+        }
+        for i in 0..12 {
+            let from = other.ep[i] as usize;
+            self.ep[i] = ep[from];
+            self.eo[i] = (eo[from] + other.eo[i]) % 2;
+        }
+    }
+
+    /// Corner orientation coordinate, 0..2187.
+    pub fn twist(&self) -> usize {
+        self.co[..7].iter().fold(0, |acc, v| acc * 3 + *v as usize)
+    }
+
+    pub fn set_twist(&mut self, mut twist: usize) {
+        let mut sum = 0;
+        for i in (0..7).rev() {
+            self.co[i] = (twist % 3) as u8;
+            sum += self.co[i];
+            twist /= 3;
+        }
+        self.co[7] = (3 - sum % 3) % 3;
+    }
+
+    /// Edge orientation coordinate, 0..2048.
+    pub fn flip(&self) -> usize {
+        self.eo[..11].iter().fold(0, |acc, v| acc * 2 + *v as usize)
+    }
+
+    pub fn set_flip(&mut self, mut flip: usize) {
+        let mut sum = 0;
+        for i in (0..11).rev() {
+            self.eo[i] = (flip % 2) as u8;
+            sum += self.eo[i];
+            flip /= 2;
+        }
+        self.eo[11] = sum % 2;
+    }
+
+    /// Which positions hold the E-slice edges (FR, FL, BL, BR), ignoring
+    /// their order, 0..495.
+    pub fn slice(&self) -> usize {
+        let mut k = 0;
+        let mut rank = 0;
+        for (i, e) in self.ep.iter().enumerate() {
+            if *e >= 8 {
+                k += 1;
+                rank += binomial(i, k);
+            }
+        }
+        rank
+    }
+
+    pub fn set_slice(&mut self, mut slice: usize) {
+        let mut in_slice = [false; 12];
+        for k in (1..=4).rev() {
+            let mut i = k - 1;
+            while binomial(i + 1, k) <= slice {
+                i += 1;
+            }
+            slice -= binomial(i, k);
+            in_slice[i] = true;
+        }
+        let (mut other, mut sliced) = (0, 8);
+        for (e, in_slice) in self.ep.iter_mut().zip(in_slice) {
+            if in_slice {
+                *e = sliced;
+                sliced += 1;
+            } else {
+                *e = other;
+                other += 1;
+            }
+        }
+    }
+
+    /// Corner permutation coordinate, 0..40320.
+    pub fn corner_perm(&self) -> usize {
+        perm_rank(&self.cp)
+    }
+
+    pub fn set_corner_perm(&mut self, rank: usize) {
+        perm_unrank(rank, &mut self.cp);
+    }
+
+    /// Permutation of the U and D layer edges, 0..40320. Only meaningful
+    /// once the E-slice edges are in the E slice.
+    pub fn ud_edge_perm(&self) -> usize {
+        perm_rank(&self.ep[..8])
+    }
+
+    pub fn set_ud_edge_perm(&mut self, rank: usize) {
+        perm_unrank(rank, &mut self.ep[..8]);
+    }
+
+    /// Permutation of the E-slice edges, 0..24. Only meaningful once they
+    /// are in the E slice.
+    pub fn slice_perm(&self) -> usize {
+        perm_rank(&self.ep[8..])
+    }
+
+    pub fn set_slice_perm(&mut self, rank: usize) {
+        perm_unrank(rank, &mut self.ep[8..]);
+        for e in &mut self.ep[8..] {
+            *e += 8;
+        }
+    }
+}
+
+/// The corner piece in corner position `i` and its twist, if its stickers
+/// make up a real corner.
+pub fn corner_at(state: &CubeState, i: usize) -> Option<(u8, u8)> {
+    let f = &state.facelets;
+    let facelets = CORNER_FACELETS[i];
+    let ori = (0..3).find(|k| matches!(f[facelets[*k]], Face::U | Face::D))?;
+    let c0 = f[facelets[ori]];
+    let c1 = f[facelets[(ori + 1) % 3]];
+    let c2 = f[facelets[(ori + 2) % 3]];
+    let piece = CORNER_COLORS
+        .iter()
+        .position(|c| c[0] == c0 && c[1] == c1 && c[2] == c2)?;
+    Some((piece as u8, ori as u8))
+}
+
+/// The edge piece in edge position `i` and its flip, if its stickers make
+/// up a real edge.
+pub fn edge_at(state: &CubeState, i: usize) -> Option<(u8, u8)> {
+    let f = &state.facelets;
+    let (c0, c1) = (f[EDGE_FACELETS[i][0]], f[EDGE_FACELETS[i][1]]);
+    EDGE_COLORS.iter().enumerate().find_map(|(j, c)| {
+        if c[0] == c0 && c[1] == c1 {
+            Some((j as u8, 0))
+        } else if c[0] == c1 && c[1] == c0 {
+            Some((j as u8, 1))
+        } else {
+            None
+        }
+    })
+}
+
+/// The face turn with index `i` in the 18 move tables: three powers for each
+/// face of `FACELET_ORDER`.
+pub fn index_move(i: usize) -> Move {
+    Move {
+        face: FACELET_ORDER[i / 3],
+        layers: Layers::Outer,
+        prime: i % 3 == 2,
+        half_turn: i % 3 == 1,
+    }
+}
+
+/// The cube reached by applying move `i` to a solved cube.
+pub fn move_cube(i: usize) -> CubieCube {
+    static MOVES: OnceLock<Vec<CubieCube>> = OnceLock::new();
+    MOVES.get_or_init(|| {
+        (0..18)
+            .map(|i| {
+                let mut state = CubeState::solved();
+                state.apply(index_move(i));
+                CubieCube::from_state(&state).unwrap()
+            })
+            .collect()
+    })[i]
+}
+
+/// Whether the permutation is odd.
+pub fn parity(p: &[u8]) -> bool {
+    let mut inversions = 0;
+    for i in 0..p.len() {
+        for j in i + 1..p.len() {
+            if p[j] < p[i] {
+                inversions += 1;
+            }
+        }
+    }
+    inversions % 2 == 1
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+fn factorial(n: usize) -> usize {
+    (1..=n).product()
+}
+
+/// Lexicographic rank of a permutation of distinct values.
+pub fn perm_rank(p: &[u8]) -> usize {
+    let n = p.len();
+    let mut rank = 0;
+    for i in 0..n {
+        let smaller = p[i + 1..].iter().filter(|v| **v < p[i]).count();
+        rank += smaller * factorial(n - 1 - i);
+    }
+    rank
+}
+
+/// Fills `p` with the permutation of `0..p.len()` of the given rank.
+pub fn perm_unrank(mut rank: usize, p: &mut [u8]) {
+    let n = p.len();
+    let mut left: Vec<u8> = (0..n as u8).collect();
+    for (i, v) in p.iter_mut().enumerate() {
+        let f = factorial(n - 1 - i);
+        *v = left.remove(rank / f);
+        rank %= f;
+    }
+}
@@ -0,0 +1,195 @@
+//! Rejecting random states that make poor scrambles: ones that are solved
+//! in very few moves or that already have a cross or a 2x2x2 block.
+
+use super::cubie::CubieCube;
+use super::solver;
+use std::fmt::{self, Formatter};
+
+/// Edges around each face, as positions in `CubieCube::ep`.
+const FACE_EDGES: [[usize; 4]; 6] = [
+    [0, 1, 2, 3],
+    [4, 5, 6, 7],
+    [0, 4, 8, 11],
+    [2, 6, 9, 10],
+    [1, 5, 8, 9],
+    [3, 7, 10, 11],
+];
+
+/// Each corner position with the three edges next to it, which make up a
+/// 2x2x2 block with the centers.
+const BLOCKS: [(usize, [usize; 3]); 8] = [
+    (0, [0, 1, 8]),
+    (1, [1, 2, 9]),
+    (2, [2, 3, 10]),
+    (3, [3, 0, 11]),
+    (4, [5, 4, 8]),
+    (5, [6, 5, 9]),
+    (6, [7, 6, 10]),
+    (7, [4, 7, 11]),
+];
+
+/// Longest accepted `min_length`. Ruling out 12 move solutions takes the
+/// solver a fraction of a second, and every move more about ten times as
+/// long.
+pub const MAX_MIN_LENGTH: usize = 13;
+
+/// What a random state scramble is checked for.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ScrambleFilter {
+    /// Cubes solvable in fewer moves than this are rejected.
+    pub min_length: usize,
+    /// Reject cubes with the cross of any color solved.
+    pub cross: bool,
+    /// Reject cubes with any 2x2x2 block solved.
+    pub block: bool,
+}
+
+impl Default for ScrambleFilter {
+    fn default() -> Self {
+        Self {
+            min_length: 11,
+            cross: true,
+            block: true,
+        }
+    }
+}
+
+/// Why the filter turned a cube down.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Rejection {
+    TooShort,
+    SolvedCross,
+    SolvedBlock,
+}
+
+/// How many cubes the filter has looked at and why it turned them down.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct FilterStats {
+    pub checked: usize,
+    pub too_short: usize,
+    pub solved_cross: usize,
+    pub solved_block: usize,
+}
+
+impl FilterStats {
+    pub fn rejected(&self) -> usize {
+        self.too_short + self.solved_cross + self.solved_block
+    }
+
+    pub fn record(&mut self, result: Result<(), Rejection>) {
+        self.checked += 1;
+        match result {
+            Ok(()) => (),
+            Err(Rejection::TooShort) => self.too_short += 1,
+            Err(Rejection::SolvedCross) => self.solved_cross += 1,
+            Err(Rejection::SolvedBlock) => self.solved_block += 1,
+        }
+    }
+
+    /// Reads the counts back from the line `Display` writes.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut counts = line.split_whitespace().map(|v| v.parse().ok());
+        let mut next = || counts.next().flatten();
+        Some(Self {
+            checked: next()?,
+            too_short: next()?,
+            solved_cross: next()?,
+            solved_block: next()?,
+        })
+    }
+}
+
+impl std::fmt::Display for FilterStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.checked, self.too_short, self.solved_cross, self.solved_block
+        )?;
+        Ok(())
+    }
+}
+
+fn edge_solved(cube: &CubieCube, i: usize) -> bool {
+    cube.ep[i] as usize == i && cube.eo[i] == 0
+}
+
+impl ScrambleFilter {
+    /// Whether `cube` makes a good scramble. The cheap checks go first.
+    pub fn check(&self, cube: &CubieCube) -> Result<(), Rejection> {
+        let cross = FACE_EDGES
+            .iter()
+            .any(|edges| edges.iter().all(|v| edge_solved(cube, *v)));
+        if self.cross && cross {
+            return Err(Rejection::SolvedCross);
+        }
+        let block = BLOCKS.iter().any(|(corner, edges)| {
+            cube.cp[*corner] as usize == *corner
+                && cube.co[*corner] == 0
+                && edges.iter().all(|v| edge_solved(cube, *v))
+        });
+        if self.block && block {
+            return Err(Rejection::SolvedBlock);
+        }
+        let min_length = self.min_length.min(MAX_MIN_LENGTH);
+        if min_length > 0 && solver::solve(cube, min_length - 1).is_some() {
+            return Err(Rejection::TooShort);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::{notation::parse_moves, CubeState};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn cube(moves: &str) -> CubieCube {
+        let mut state = CubeState::solved();
+        state.apply_all(&parse_moves(moves).unwrap());
+        CubieCube::from_state(&state).unwrap()
+    }
+
+    #[test]
+    fn rejects_short_and_blocky_cubes() {
+        let filter = ScrambleFilter::default();
+        let only_length = ScrambleFilter {
+            cross: false,
+            block: false,
+            ..filter
+        };
+        // U keeps the D cross, R U and F turns keep the block around DBL
+        assert_eq!(filter.check(&cube("U")), Err(Rejection::SolvedCross));
+        assert_eq!(only_length.check(&cube("U")), Err(Rejection::TooShort));
+        assert_eq!(
+            filter.check(&cube("R U F R' U2 F' R2 U'")),
+            Err(Rejection::SolvedBlock)
+        );
+        let scramble = "R U2 F' L D B2 R' U F2 D' L2 B U' R2 F";
+        assert_eq!(only_length.check(&cube(scramble)), Ok(()));
+    }
+
+    #[test]
+    fn random_cubes_usually_pass() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut stats = FilterStats::default();
+        for _ in 0..20 {
+            stats.record(ScrambleFilter::default().check(&CubieCube::random(&mut rng)));
+        }
+        assert_eq!(stats.checked, 20);
+        assert_eq!(stats.rejected(), 0);
+    }
+
+    #[test]
+    fn reads_back_written_stats() {
+        let mut stats = FilterStats::default();
+        stats.record(Ok(()));
+        stats.record(Err(Rejection::TooShort));
+        stats.record(Err(Rejection::SolvedBlock));
+        assert_eq!(stats.to_string(), "3 1 0 1");
+        assert_eq!(FilterStats::parse(&stats.to_string()), Some(stats));
+        assert_eq!(FilterStats::parse("3 1 0"), None);
+        assert_eq!(FilterStats::parse("3 1 x 1"), None);
+    }
+}
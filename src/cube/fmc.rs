@@ -0,0 +1,135 @@
+//! Fewest moves: padded scrambles and checking a written solution the way
+//! a WCA judge would.
+
+use super::cubie::CubieCube;
+use super::notation::{parse_moves, ParseError};
+use super::sequence::Metric;
+use super::{scramble_to, CubeState, Face, Layers, Move};
+use rand::Rng;
+use std::fmt::{self, Formatter};
+
+/// Longest solution accepted, in OBTM.
+pub const MAX_MOVES: usize = 80;
+
+fn padding() -> [Move; 3] {
+    [(Face::R, true), (Face::U, true), (Face::F, false)].map(|(face, prime)| Move {
+        face,
+        layers: Layers::Outer,
+        prime,
+        half_turn: false,
+    })
+}
+
+/// Axis a face turns around, to tell whether two moves can cancel.
+fn axis(face: Face) -> Face {
+    match face {
+        Face::L => Face::R,
+        Face::B => Face::F,
+        Face::D => Face::U,
+        v => v,
+    }
+}
+
+/// A random state scramble between two `R' U' F`, as in official FMC
+/// scrambles. States whose scramble would cancel into the padding are
+/// skipped, so the padding can't be undone for free.
+pub fn gen_scramble<R: Rng + ?Sized>(rng: &mut R) -> Vec<Move> {
+    let padding = padding();
+    loop {
+        let inner = scramble_to(&CubieCube::random(rng));
+        let (first, last) = (inner[0], inner[inner.len() - 1]);
+        if axis(first.face) != axis(padding[2].face) && axis(last.face) != axis(padding[0].face) {
+            return [&padding[..], &inner, &padding[..]].concat();
+        }
+    }
+}
+
+/// Why an FMC solution is not accepted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FmcError {
+    /// The solution is not written in WCA notation.
+    Notation(ParseError),
+    /// Slice moves are not allowed, only outer block turns and rotations.
+    Slice(Move),
+    /// This many moves, more than `MAX_MOVES`.
+    TooLong(usize),
+    /// The solution leaves the cube unsolved.
+    NotSolved,
+}
+
+impl std::fmt::Display for FmcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            FmcError::Notation(e) => write!(f, "{}", e)?,
+            FmcError::Slice(mv) => write!(f, "slice moves like `{}` are not allowed", mv)?,
+            FmcError::TooLong(n) => write!(f, "{} moves, more than {}", n, MAX_MOVES)?,
+            FmcError::NotSolved => write!(f, "the cube is not solved at the end")?,
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FmcError {}
+
+/// Reads a written solution to `scramble` and returns its moves if it is
+/// legal and solves the cube, in any orientation.
+pub fn check(scramble: &[Move], solution: &str) -> Result<Vec<Move>, FmcError> {
+    let moves = parse_moves(solution).map_err(FmcError::Notation)?;
+    if let Some(mv) = moves.iter().find(|v| v.layers == Layers::Slice) {
+        return Err(FmcError::Slice(*mv));
+    }
+    let n = Metric::Obtm.count(&moves);
+    if n > MAX_MOVES {
+        return Err(FmcError::TooLong(n));
+    }
+    let mut state = CubeState::solved();
+    state.apply_all(scramble);
+    state.apply_all(&moves);
+    match state.is_solved() {
+        true => Ok(moves),
+        false => Err(FmcError::NotSolved),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::{moves_to_string, sequence::simplify};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn scrambles_are_padded_without_cancelling() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..5 {
+            let moves = gen_scramble(&mut rng);
+            let text = moves_to_string(&moves);
+            assert!(text.starts_with("R' U' F ") && text.ends_with(" R' U' F"));
+            assert_eq!(simplify(&moves), moves);
+        }
+    }
+
+    #[test]
+    fn accepts_solutions_in_any_orientation() {
+        let scramble = parse_moves("R' U' F R U R' U' F2 R' U' F").unwrap();
+        let solution = "F' U R F2 U R U' R' F' U R";
+        assert_eq!(Metric::Obtm.count(&check(&scramble, solution).unwrap()), 11);
+        let rotated = "F' U R F2 U R U' R' F' U R x y";
+        assert_eq!(Metric::Obtm.count(&check(&scramble, rotated).unwrap()), 11);
+    }
+
+    #[test]
+    fn rejects_illegal_and_wrong_solutions() {
+        let scramble = parse_moves("R' U' F R' U' F").unwrap();
+        assert!(matches!(
+            check(&scramble, "F' U R F'"),
+            Err(FmcError::NotSolved)
+        ));
+        assert!(matches!(
+            check(&scramble, "F' U R3"),
+            Err(FmcError::Notation(_))
+        ));
+        assert!(matches!(check(&scramble, "M M'"), Err(FmcError::Slice(_))));
+        let long = "R R' ".repeat(41);
+        assert_eq!(check(&scramble, &long), Err(FmcError::TooLong(82)));
+    }
+}
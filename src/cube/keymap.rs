@@ -0,0 +1,120 @@
+//! Keys turning the virtual cube, written csTimer style as `J=U F=U' I=R`.
+
+use super::notation::parse_move;
+use super::Move;
+use std::fmt::{self, Formatter};
+
+/// csTimer's keyboard layout: right hand on `U R F` turns and their wide
+/// and slice versions, left hand on the inverses.
+pub const CSTIMER_KEYS: &str = "J=U F=U' I=R K=R' H=F G=F' S=D L=D' D=L E=L' W=B O=B' \
+    U=r M=r' V=l R=l' ,=u C=u' Z=d /=d' 5=M 6=M X=M' .=M' \
+    T=x Y=x B=x' N=x' ;=y A=y' P=z Q=z'";
+
+/// Which move each key makes. Keys are case insensitive.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyMap {
+    keys: Vec<(char, Move)>,
+}
+
+/// A binding that can't be read.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KeyMapError {
+    /// Not a single key, `=` and a move.
+    Binding(String),
+    /// The move after `=` is not in WCA notation.
+    Move(String),
+}
+
+impl std::fmt::Display for KeyMapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            KeyMapError::Binding(v) => write!(f, "`{}` is not written as key=move", v)?,
+            KeyMapError::Move(v) => write!(f, "unknown move in `{}`", v)?,
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for KeyMapError {}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self::parse(CSTIMER_KEYS).expect("csTimer keys parse")
+    }
+}
+
+impl std::fmt::Display for KeyMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let bindings: Vec<String> = self
+            .keys
+            .iter()
+            .map(|(key, mv)| format!("{}={}", key.to_ascii_uppercase(), mv))
+            .collect();
+        write!(f, "{}", bindings.join(" "))?;
+        Ok(())
+    }
+}
+
+impl KeyMap {
+    /// Reads whitespace separated `key=move` bindings.
+    pub fn parse(s: &str) -> Result<Self, KeyMapError> {
+        Self { keys: vec![] }.with(s)
+    }
+
+    /// This key map with the bindings in `s` added, replacing the ones for
+    /// the same keys.
+    pub fn with(&self, s: &str) -> Result<Self, KeyMapError> {
+        let mut keys = self.keys.clone();
+        for binding in s.split_whitespace() {
+            let mut chars = binding.chars();
+            let (key, mv) = match (chars.next(), chars.next()) {
+                (Some(key), Some('=')) => (key.to_ascii_lowercase(), chars.as_str()),
+                _ => return Err(KeyMapError::Binding(binding.to_string())),
+            };
+            let mv = parse_move(mv).map_err(|_| KeyMapError::Move(binding.to_string()))?;
+            match keys.iter_mut().find(|(k, _)| *k == key) {
+                Some(v) => v.1 = mv,
+                None => keys.push((key, mv)),
+            }
+        }
+        Ok(Self { keys })
+    }
+
+    /// The move `key` makes, if it is bound.
+    pub fn get(&self, key: char) -> Option<Move> {
+        let key = key.to_ascii_lowercase();
+        self.keys.iter().find(|(k, _)| *k == key).map(|(_, mv)| *mv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::moves_to_string;
+
+    #[test]
+    fn cstimer_keys_by_default() {
+        let keys = KeyMap::default();
+        let moves: Vec<Move> = "jfikhg".chars().filter_map(|v| keys.get(v)).collect();
+        assert_eq!(moves_to_string(&moves), "U U' R R' F F'");
+        assert_eq!(keys.get('J'), keys.get('j'));
+        assert_eq!(keys.get('1'), None);
+        assert_eq!(KeyMap::parse(&keys.to_string()), Ok(keys));
+    }
+
+    #[test]
+    fn typed_bindings_replace_old_ones() {
+        let keys = KeyMap::default().with("j=U2 1=R2").unwrap();
+        assert_eq!(keys.get('j').unwrap().to_string(), "U2");
+        assert_eq!(keys.get('1').unwrap().to_string(), "R2");
+        assert_eq!(keys.get('f').unwrap().to_string(), "U'");
+        assert_eq!(
+            KeyMap::default().with("jU"),
+            Err(KeyMapError::Binding("jU".to_string()))
+        );
+        assert_eq!(
+            KeyMap::default().with("J=Q"),
+            Err(KeyMapError::Move("J=Q".to_string()))
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Recognizing last layer cases once F2L is solved: which of the 57 OLL
+//! and 21 PLL cases the cube shows, the AUF to line it up with the stored
+//! algorithm and the AUF after it.
+
+use super::notation::parse_moves;
+use super::sequence::invert;
+use super::{CubeState, Face, Layers, Move, FACELET_ORDER};
+
+/// OLL algorithms by case number, `OLLS[i]` solving OLL `i + 1`.
+pub const OLLS: [&str; 57] = [
+    "R U2 R2 F R F' U2 R' F R F'",
+    "F R U R' U' F' f R U R' U' f'",
+    "f R U R' U' f' U' F R U R' U' F'",
+    "f R U R' U' f' U F R U R' U' F'",
+    "r' U2 R U R' U r",
+    "r U2 R' U' R U' r'",
+    "r U R' U R U2 r'",
+    "l' U' L U' L' U2 l",
+    "R U R' U' R' F R2 U R' U' F'",
+    "R U R' U R' F R F' R U2 R'",
+    "r U R' U R' F R F' R U2 r'",
+    "M' R' U' R U' R' U2 R U' R r'",
+    "F U R U' R2 F' R U R U' R'",
+    "R' F R U R' F' R F U' F'",
+    "r' U' r R' U' R U r' U r",
+    "r U r' R U R' U' r U' r'",
+    "R U R' U R' F R F' U2 R' F R F'",
+    "r U R' U R U2 r2 U' R U' R' U2 r",
+    "r' R U R U R' U' M' R' F R F'",
+    "r U R' U' M2 U R U' R' U' M'",
+    "R U2 R' U' R U R' U' R U' R'",
+    "R U2 R2 U' R2 U' R2 U2 R",
+    "R2 D' R U2 R' D R U2 R",
+    "r U R' U' r' F R F'",
+    "F' r U R' U' r' F R",
+    "R U2 R' U' R U' R'",
+    "R U R' U R U2 R'",
+    "r U R' U' M U R U' R'",
+    "R U R' U' R U' R' F' U' F R U R'",
+    "F R' F R2 U' R' U' R U R' F2",
+    "R' U' F U R U' R' F' R",
+    "S R U R' U' R' F R f'",
+    "R U R' U' R' F R F'",
+    "R U R2 U' R' F R U R U' F'",
+    "R U2 R2 F R F' R U2 R'",
+    "L' U' L U' L' U L U L F' L' F",
+    "F R' F' R U R U' R'",
+    "R U R' U R U' R' U' R' F R F'",
+    "L F' L' U' L U F U' L'",
+    "R' F R U R' U' F' U R",
+    "R U R' U R U2 R' F R U R' U' F'",
+    "R' U' R U' R' U2 R F R U R' U' F'",
+    "F' U' L' U L F",
+    "F U R U' R' F'",
+    "F R U R' U' F'",
+    "R' U' R' F R F' U R",
+    "R' U' R' F R F' R' F R F' U R",
+    "F R U R' U' R U R' U' F'",
+    "r U' r2 U r2 U r2 U' r",
+    "r' U r2 U' r2 U' r2 U r'",
+    "F U R U' R' U R U' R' F'",
+    "R U R' U R U' B U' B' R'",
+    "l' U2 L U L' U' L U L' U l",
+    "r U2 R' U' R U R' U' R U' r'",
+    "R' F R U R U' R2 F' R2 U' R' U R U R'",
+    "r' U' r U' R' U R U' R' U R r' U r",
+    "R U R' U' M' U R U' r'",
+];
+
+/// PLL algorithms by the usual letter names.
+pub const PLLS: [(&str, &str); 21] = [
+    ("Aa", "x R' U R' D2 R U' R' D2 R2 x'"),
+    ("Ab", "x R2 D2 R U R' D2 R U' R x'"),
+    ("E", "x' R U' R' D R U R' D' R U R' D R U' R' D' x"),
+    ("F", "R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R"),
+    ("Ga", "R2 U R' U R' U' R U' R2 U' D R' U R D'"),
+    ("Gb", "R' U' R U D' R2 U R' U R U' R U' R2 D"),
+    ("Gc", "R2 U' R U' R U R' U R2 U D' R U' R' D"),
+    ("Gd", "R U R' U' D R2 U' R U' R' U R' U R2 D'"),
+    ("H", "M2 U M2 U2 M2 U M2"),
+    ("Ja", "x R2 F R F' R U2 r' U r U2 x'"),
+    ("Jb", "R U R' F' R U R' U' R' F R2 U' R'"),
+    (
+        "Na",
+        "R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'",
+    ),
+    ("Nb", "R' U R U' R' F' U' F R U R' F R' F' R U' R"),
+    ("Ra", "R U' R' U' R U R D R' U' R D' R' U2 R'"),
+    ("Rb", "R2 F R U R U' R' F' R U2 R' U2 R"),
+    ("T", "R U R' U' R' F R2 U' R' U' R U R' F'"),
+    ("Ua", "M2 U M U2 M' U M2"),
+    ("Ub", "M2 U' M U2 M' U' M2"),
+    ("V", "R' U R' U' y R' F' R2 U' R' U R' F R F"),
+    ("Y", "F R U' R' U' R U R' F' R U R' U' R' F R F'"),
+    ("Z", "M' U M2 U M2 U M' U2 M2"),
+];
+
+/// A recognized case: the AUF before the algorithm, the algorithm, and
+/// for PLL the AUF after it. Skips have no algorithm.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Case {
+    pub name: String,
+    pub pre: Option<Move>,
+    pub alg: Vec<Move>,
+    pub post: Option<Move>,
+}
+
+impl Case {
+    /// Everything to do, AUFs included.
+    pub fn moves(&self) -> Vec<Move> {
+        let mut moves: Vec<Move> = self.pre.into_iter().collect();
+        moves.extend(&self.alg);
+        moves.extend(self.post);
+        moves
+    }
+}
+
+/// U turns by quarter turns: none, `U`, `U2` and `U'`.
+fn auf(quarter_turns: usize) -> Option<Move> {
+    let quarter_turns = quarter_turns % 4;
+    (quarter_turns != 0).then_some(Move {
+        face: Face::U,
+        layers: Layers::Outer,
+        prime: quarter_turns == 3,
+        half_turn: quarter_turns == 2,
+    })
+}
+
+/// The cube after an AUF of `pre` quarter turns, `alg` and an AUF of `post`.
+fn after(state: &CubeState, pre: usize, alg: &[Move], post: usize) -> CubeState {
+    let mut state = *state;
+    state.apply_all(&auf(pre).into_iter().collect::<Vec<Move>>());
+    state.apply_all(alg);
+    state.apply_all(&auf(post).into_iter().collect::<Vec<Move>>());
+    state
+}
+
+/// Whether the D layer and the middle layer match their centers. Stickers
+/// are compared with centers rather than face names, so rotations about the
+/// U-D axis are fine.
+pub fn f2l_solved(state: &CubeState) -> bool {
+    FACELET_ORDER.iter().all(|face| {
+        let stickers = state.face(*face);
+        let solved = match face {
+            Face::U => 0..0,
+            Face::D => 0..9,
+            _ => 3..9,
+        };
+        stickers[solved].iter().all(|v| *v == stickers[4])
+    })
+}
+
+fn oriented(state: &CubeState) -> bool {
+    let stickers = state.face(Face::U);
+    stickers.iter().all(|v| *v == stickers[4])
+}
+
+/// The OLL case of a cube with F2L solved, or `None` if F2L is not solved.
+pub fn oll_case(state: &CubeState) -> Option<Case> {
+    if !f2l_solved(state) {
+        return None;
+    }
+    if oriented(state) {
+        return Some(Case {
+            name: "OLL skip".to_string(),
+            pre: None,
+            alg: vec![],
+            post: None,
+        });
+    }
+    OLLS.iter().enumerate().find_map(|(i, alg)| {
+        let alg = parse_moves(alg).expect("OLL algorithms parse");
+        (0..4).find_map(|pre| {
+            oriented(&after(state, pre, &alg, 0)).then(|| Case {
+                name: format!("OLL {}", i + 1),
+                pre: auf(pre),
+                alg: alg.clone(),
+                post: None,
+            })
+        })
+    })
+}
+
+/// The PLL case of a cube with F2L solved and the last layer oriented, or
+/// `None` if it isn't that far.
+pub fn pll_case(state: &CubeState) -> Option<Case> {
+    if !f2l_solved(state) || !oriented(state) {
+        return None;
+    }
+    if let Some(post) = (0..4).find(|v| after(state, 0, &[], *v).is_solved()) {
+        return Some(Case {
+            name: "PLL skip".to_string(),
+            pre: None,
+            alg: vec![],
+            post: auf(post),
+        });
+    }
+    PLLS.iter().find_map(|(name, alg)| {
+        let alg = parse_moves(alg).expect("PLL algorithms parse");
+        (0..4).find_map(|pre| {
+            (0..4).find_map(|post| {
+                after(state, pre, &alg, post).is_solved().then(|| Case {
+                    name: format!("{} perm", name),
+                    pre: auf(pre),
+                    alg: alg.clone(),
+                    post: auf(post),
+                })
+            })
+        })
+    })
+}
+
+/// The stored algorithm of a case named like `OLL 27`, `T` or `Ua perm`,
+/// in any case.
+pub fn named_alg(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_ascii_lowercase();
+    if let Some(number) = name.strip_prefix("oll") {
+        let i = number.trim().parse::<usize>().ok()?.checked_sub(1)?;
+        return OLLS.get(i).copied();
+    }
+    let name = name.strip_prefix("pll").unwrap_or(&name).trim();
+    let name = name.strip_suffix("perm").unwrap_or(name).trim();
+    PLLS.iter()
+        .find(|(v, _)| v.to_ascii_lowercase() == name)
+        .map(|(_, alg)| *alg)
+}
+
+/// The cube a stored algorithm solves, set up from a solved cube.
+pub fn case_state(alg: &str) -> CubeState {
+    let mut state = CubeState::solved();
+    state.apply_all(&invert(&parse_moves(alg).expect("algorithms parse")));
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_oll_is_its_own_case() {
+        for (i, alg) in OLLS.iter().enumerate() {
+            let state = case_state(alg);
+            assert!(f2l_solved(&state), "OLL {}", i + 1);
+            let case = oll_case(&state).unwrap();
+            assert_eq!(case.name, format!("OLL {}", i + 1));
+            assert_eq!(case.pre, None);
+        }
+        let mut state = case_state(OLLS[26]);
+        state.apply(auf(1).unwrap());
+        let case = oll_case(&state).unwrap();
+        assert_eq!((case.name.as_str(), case.pre), ("OLL 27", auf(3)));
+    }
+
+    #[test]
+    fn every_pll_is_its_own_case() {
+        for (name, alg) in PLLS {
+            let state = case_state(alg);
+            assert!(f2l_solved(&state) && oriented(&state), "{}", name);
+            assert_eq!(pll_case(&state).unwrap().name, format!("{} perm", name));
+        }
+        let mut state = case_state(PLLS[15].1);
+        state.apply_all(&parse_moves("U2").unwrap());
+        let case = pll_case(&state).unwrap();
+        assert_eq!(case.name, "T perm");
+        let mut solved = state;
+        solved.apply_all(&case.moves());
+        assert!(solved.is_solved());
+    }
+
+    #[test]
+    fn finds_algorithms_by_name() {
+        assert_eq!(named_alg("OLL 27"), Some(OLLS[26]));
+        assert_eq!(named_alg(" oll57"), Some(OLLS[56]));
+        assert_eq!(named_alg("t"), Some(PLLS[15].1));
+        assert_eq!(named_alg("PLL Ua perm"), Some(PLLS[16].1));
+        assert_eq!(named_alg("OLL 58"), None);
+        assert_eq!(named_alg("OLL 0"), None);
+        assert_eq!(named_alg("R U R'"), None);
+    }
+
+    #[test]
+    fn solves_mixed_last_layers() {
+        // Every OLL with some PLL before it and AUFs in between
+        for (i, oll) in OLLS.iter().enumerate() {
+            let mut state = case_state(PLLS[i % PLLS.len()].1);
+            state.apply_all(&auf(i).into_iter().collect::<Vec<Move>>());
+            state.apply_all(&invert(&parse_moves(oll).unwrap()));
+            state.apply_all(&auf(i / 4).into_iter().collect::<Vec<Move>>());
+            let oll = oll_case(&state).unwrap();
+            state.apply_all(&oll.moves());
+            let pll = pll_case(&state).unwrap();
+            state.apply_all(&pll.moves());
+            assert!(state.is_solved(), "{} {}", oll.name, pll.name);
+        }
+        let mut state = CubeState::solved();
+        state.apply_all(&parse_moves("R U R'").unwrap());
+        assert_eq!(oll_case(&state), None);
+    }
+}
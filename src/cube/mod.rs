@@ -0,0 +1,512 @@
+pub mod bld;
+pub mod cross;
+pub mod cube2;
+pub mod cubie;
+pub mod filter;
+pub mod fmc;
+pub mod keymap;
+pub mod ll;
+pub mod notation;
+pub mod nxn;
+pub mod pattern;
+pub mod recon;
+pub mod sequence;
+pub mod solve;
+pub mod subset;
+pub mod validate;
+pub mod solver;
+
+use cubie::CubieCube;
+use filter::{FilterStats, ScrambleFilter};
+use validate::InvalidState;
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+
+use std::{
+    fmt::{self, Formatter},
+    sync::OnceLock,
+};
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum Face {
+    U,
+    L,
+    F,
+    R,
+    B,
+    D,
+    None,
+}
+
+impl std::fmt::Display for Face {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let c = match self {
+            Face::U => 'U',
+            Face::L => 'L',
+            Face::F => 'F',
+            Face::R => 'R',
+            Face::B => 'B',
+            Face::D => 'D',
+            Face::None => unreachable!(),
+        };
+        write!(f, "{}", c)?;
+        Ok(())
+    }
+}
+
+impl Face {
+    /// Outward normal, row direction and column direction of the face when
+    /// looking straight at it, in a frame where +x is R, +y is U and +z is F.
+    fn axes(&self) -> ([i32; 3], [i32; 3], [i32; 3]) {
+        match self {
+            Face::U => ([0, 1, 0], [0, 0, 1], [1, 0, 0]),
+            Face::R => ([1, 0, 0], [0, -1, 0], [0, 0, -1]),
+            Face::F => ([0, 0, 1], [0, -1, 0], [1, 0, 0]),
+            Face::D => ([0, -1, 0], [0, 0, -1], [1, 0, 0]),
+            Face::L => ([-1, 0, 0], [0, -1, 0], [0, 0, 1]),
+            Face::B => ([0, 0, -1], [0, -1, 0], [-1, 0, 0]),
+            Face::None => unreachable!(),
+        }
+    }
+}
+
+/// Which layers of the cube a move turns, always in the direction of
+/// `Move::face`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Layers {
+    /// Just the face, `R`.
+    Outer,
+    /// The face and the middle layer behind it, `Rw` or `r`.
+    Wide,
+    /// Only the middle layer: `M` turns like L, `E` like D and `S` like F.
+    Slice,
+    /// The whole cube: `x` turns like R, `y` like U and `z` like F.
+    Rotation,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Move {
+    pub face: Face,
+    pub layers: Layers,
+    pub prime: bool,
+    pub half_turn: bool,
+}
+
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let primestr = match self.prime {
+            true => "\'",
+            false => "",
+        };
+        let halfstr = match self.half_turn {
+            true => "2",
+            false => "",
+        };
+        match self.layers {
+            Layers::Outer => write!(f, "{}", self.face)?,
+            Layers::Wide => write!(f, "{}w", self.face)?,
+            Layers::Slice | Layers::Rotation => {
+                let c = match (self.layers, self.face) {
+                    (Layers::Slice, Face::L) => 'M',
+                    (Layers::Slice, Face::D) => 'E',
+                    (Layers::Slice, Face::F) => 'S',
+                    (Layers::Rotation, Face::R) => 'x',
+                    (Layers::Rotation, Face::U) => 'y',
+                    (Layers::Rotation, Face::F) => 'z',
+                    _ => unreachable!(),
+                };
+                write!(f, "{}", c)?
+            }
+        }
+        write!(f, "{}{}", halfstr, primestr)?;
+        Ok(())
+    }
+}
+
+impl Move {
+    /// Number of clockwise quarter turns the move is equivalent to.
+    pub fn quarter_turns(&self) -> usize {
+        match (self.half_turn, self.prime) {
+            (true, _) => 2,
+            (false, true) => 3,
+            (false, false) => 1,
+        }
+    }
+
+    pub fn inverse(&self) -> Move {
+        Move {
+            face: self.face,
+            layers: self.layers,
+            prime: !self.prime && !self.half_turn,
+            half_turn: self.half_turn,
+        }
+    }
+}
+
+impl Distribution<Move> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Move {
+        let face = match rng.gen_range(0..6) {
+            0 => Face::U,
+            1 => Face::L,
+            2 => Face::F,
+            3 => Face::R,
+            4 => Face::B,
+            5 => Face::D,
+            _ => unreachable!(),
+        };
+        let (prime, half_turn) = match rng.gen_range(0..3) {
+            0 => (false, false),
+            1 => (true, false),
+            2 => (false, true),
+            _ => unreachable!(),
+        };
+        Move {
+            face,
+            layers: Layers::Outer,
+            prime,
+            half_turn,
+        }
+    }
+}
+
+/// Order in which faces are stored in a `CubeState`. This is the order used
+/// by Kociemba facelet strings, so a state can be written out as one directly.
+pub const FACELET_ORDER: [Face; 6] = [Face::U, Face::R, Face::F, Face::D, Face::L, Face::B];
+
+/// A 3x3 cube described by the color of each of its 54 stickers.
+///
+/// Facelets are stored face by face in `FACELET_ORDER`, each face row by row
+/// as seen when looking straight at it with U (or F, for the U and D faces)
+/// at the top, the same layout as a Kociemba facelet string.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CubeState {
+    pub facelets: [Face; 54],
+}
+
+impl CubeState {
+    pub fn solved() -> Self {
+        let mut facelets = [Face::None; 54];
+        for (i, face) in FACELET_ORDER.iter().enumerate() {
+            facelets[i * 9..(i + 1) * 9].fill(*face);
+        }
+        Self { facelets }
+    }
+
+    /// Reads a Kociemba facelet string: 54 face letters in `FACELET_ORDER`,
+    /// e.g. `UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB` for a
+    /// solved cube. Whitespace is ignored. The stickers are only read, see
+    /// `validate` for whether they make up a solvable cube.
+    pub fn from_facelet_string(s: &str) -> Result<Self, InvalidState> {
+        let faces = s
+            .chars()
+            .filter(|v| !v.is_whitespace())
+            .map(|v| match v.to_ascii_uppercase() {
+                'U' => Ok(Face::U),
+                'R' => Ok(Face::R),
+                'F' => Ok(Face::F),
+                'D' => Ok(Face::D),
+                'L' => Ok(Face::L),
+                'B' => Ok(Face::B),
+                _ => Err(InvalidState::UnknownColor(v)),
+            })
+            .collect::<Result<Vec<Face>, InvalidState>>()?;
+        let n = faces.len();
+        Ok(Self {
+            facelets: faces.try_into().map_err(|_| InvalidState::Length(n))?,
+        })
+    }
+
+    /// Stickers of one face, row by row.
+    pub fn face(&self, face: Face) -> &[Face] {
+        let i = face_index(face);
+        &self.facelets[i * 9..(i + 1) * 9]
+    }
+
+    pub fn apply(&mut self, mv: Move) {
+        let perm = &layer_turns()[mv.layers as usize][face_index(mv.face)];
+        for _ in 0..mv.quarter_turns() {
+            let old = self.facelets;
+            for (from, to) in perm.iter().enumerate() {
+                self.facelets[*to] = old[from];
+            }
+        }
+    }
+
+    pub fn apply_all(&mut self, moves: &[Move]) {
+        for mv in moves {
+            self.apply(*mv);
+        }
+    }
+
+    /// The same state seen from the standard orientation: stickers are
+    /// renamed after the face whose center shares their color, undoing any
+    /// rotations and slice moves.
+    pub fn with_centers_fixed(&self) -> Self {
+        let mut renamed = [Face::None; 6];
+        for face in FACELET_ORDER {
+            renamed[face_index(self.face(face)[4])] = face;
+        }
+        let mut state = *self;
+        for v in &mut state.facelets {
+            *v = renamed[face_index(*v)];
+        }
+        state
+    }
+
+    /// Whether every face shows a single color.
+    pub fn is_solved(&self) -> bool {
+        self.facelets
+            .chunks(9)
+            .all(|face| face.iter().all(|v| *v == face[0]))
+    }
+}
+
+fn face_index(face: Face) -> usize {
+    FACELET_ORDER
+        .iter()
+        .position(|v| *v == face)
+        .expect("Face::None has no facelets")
+}
+
+/// Position of the center of sticker `i` on an `n` layer cube, scaled so
+/// that cubies sit at -(n - 1), -(n - 3), ..., n - 1 along each axis and the
+/// stickers themselves at +-n along their normal.
+fn facelet_position(n: usize, i: usize) -> [i32; 3] {
+    let (normal, row, col) = FACELET_ORDER[i / (n * n)].axes();
+    let (r, c) = ((i % (n * n) / n) as i32, (i % n) as i32);
+    let n = n as i32;
+    let mut p = [0; 3];
+    for k in 0..3 {
+        p[k] = normal[k] * n + row[k] * (2 * r - n + 1) + col[k] * (2 * c - n + 1);
+    }
+    p
+}
+
+/// Index of the sticker at position `p`, the inverse of `facelet_position`.
+fn facelet_index(n: usize, p: [i32; 3]) -> usize {
+    let dot = |a: [i32; 3]| a[0] * p[0] + a[1] * p[1] + a[2] * p[2];
+    let (f, (_, row, col)) = FACELET_ORDER
+        .iter()
+        .map(|v| v.axes())
+        .enumerate()
+        .find(|(_, (normal, _, _))| dot(*normal) == n as i32)
+        .expect("not a sticker position");
+    let r = ((dot(row) + n as i32 - 1) / 2) as usize;
+    let c = ((dot(col) + n as i32 - 1) / 2) as usize;
+    f * n * n + r * n + c
+}
+
+/// How far into the cube from `face` the sticker at `p` is, n for stickers on
+/// the face itself down to -n for stickers on the opposite face.
+fn facelet_depth(face: Face, p: [i32; 3]) -> i32 {
+    let (normal, _, _) = face.axes();
+    (0..3).map(|k| normal[k] * p[k]).sum()
+}
+
+/// Rotates `v` a quarter turn clockwise as seen looking down `axis` from
+/// outside the cube.
+fn rotate_cw(v: [i32; 3], axis: [i32; 3]) -> [i32; 3] {
+    let dot = axis[0] * v[0] + axis[1] * v[1] + axis[2] * v[2];
+    let cross = [
+        axis[1] * v[2] - axis[2] * v[1],
+        axis[2] * v[0] - axis[0] * v[2],
+        axis[0] * v[1] - axis[1] * v[0],
+    ];
+    [
+        axis[0] * dot - cross[0],
+        axis[1] * dot - cross[1],
+        axis[2] * dot - cross[2],
+    ]
+}
+
+/// For each kind of `Layers` and each face in `FACELET_ORDER`, where every
+/// facelet goes on a clockwise quarter turn of those layers.
+fn layer_turns() -> &'static [[[usize; 54]; 6]; 4] {
+    static TURNS: OnceLock<[[[usize; 54]; 6]; 4]> = OnceLock::new();
+    TURNS.get_or_init(|| {
+        let positions: Vec<[i32; 3]> = (0..54).map(|i| facelet_position(3, i)).collect();
+        let mut turns = [[[0; 54]; 6]; 4];
+        for (l, layers) in [Layers::Outer, Layers::Wide, Layers::Slice, Layers::Rotation]
+            .iter()
+            .enumerate()
+        {
+            for (f, face) in FACELET_ORDER.iter().enumerate() {
+                let (normal, _, _) = face.axes();
+                for (i, p) in positions.iter().enumerate() {
+                    let depth = facelet_depth(*face, *p);
+                    let turned = match layers {
+                        Layers::Outer => depth >= 2,
+                        Layers::Wide => depth >= 0,
+                        Layers::Slice => depth == 0,
+                        Layers::Rotation => true,
+                    };
+                    turns[l][f][i] = if turned {
+                        facelet_index(3, rotate_cw(*p, normal))
+                    } else {
+                        i
+                    };
+                }
+            }
+        }
+        turns
+    })
+}
+
+/// A scramble taking a solved cube to `cube`, found by undoing a two-phase
+/// solution to it. The solver never turns the same face twice in a row or
+/// opposite faces out of order, so nothing in the scramble cancels.
+pub fn scramble_to(cube: &CubieCube) -> Vec<Move> {
+    let solution = solver::solve(cube, 21).expect("every cube has a 21 move solution");
+    sequence::invert(&solution)
+}
+
+/// A random state scramble to a cube that passes `filter`. Every cube
+/// tried is recorded in `stats`.
+pub fn filtered_scramble<R: Rng + ?Sized>(
+    rng: &mut R,
+    filter: &ScrambleFilter,
+    stats: &mut FilterStats,
+) -> Vec<Move> {
+    loop {
+        let cube = CubieCube::random(rng);
+        let result = filter.check(&cube);
+        stats.record(result);
+        if result.is_ok() {
+            return scramble_to(&cube);
+        }
+    }
+}
+
+/// Formats a move sequence the way scrambles are usually written.
+pub fn moves_to_string(moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn mv(face: Face, prime: bool, half_turn: bool) -> Move {
+        Move {
+            face,
+            layers: Layers::Outer,
+            prime,
+            half_turn,
+        }
+    }
+
+    fn all_moves() -> Vec<Move> {
+        FACELET_ORDER
+            .iter()
+            .flat_map(|f| [mv(*f, false, false), mv(*f, true, false), mv(*f, false, true)])
+            .collect()
+    }
+
+    #[test]
+    fn solved_is_solved() {
+        assert!(CubeState::solved().is_solved());
+    }
+
+    #[test]
+    fn single_moves_unsolve() {
+        for m in all_moves() {
+            let mut cube = CubeState::solved();
+            cube.apply(m);
+            assert!(!cube.is_solved(), "{} left the cube solved", m);
+        }
+    }
+
+    #[test]
+    fn quarter_turn_has_order_four() {
+        for face in FACELET_ORDER {
+            let mut cube = CubeState::solved();
+            for i in 1..=4 {
+                cube.apply(mv(face, false, false));
+                assert_eq!(cube.is_solved(), i == 4);
+            }
+        }
+    }
+
+    #[test]
+    fn move_then_inverse_is_identity() {
+        for m in all_moves() {
+            let mut cube = CubeState::solved();
+            cube.apply(m);
+            cube.apply(m.inverse());
+            assert!(cube.is_solved(), "{} {} is not the identity", m, m.inverse());
+        }
+    }
+
+    #[test]
+    fn sequence_then_inverse_is_identity() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..50 {
+            let moves: Vec<Move> = (0..30).map(|_| rng.gen()).collect();
+            let inverse: Vec<Move> = moves.iter().rev().map(|m| m.inverse()).collect();
+            let mut cube = CubeState::solved();
+            cube.apply_all(&moves);
+            cube.apply_all(&inverse);
+            assert!(cube.is_solved());
+        }
+    }
+
+    #[test]
+    fn sexy_move_has_order_six() {
+        let sexy = [
+            mv(Face::R, false, false),
+            mv(Face::U, false, false),
+            mv(Face::R, true, false),
+            mv(Face::U, true, false),
+        ];
+        let mut cube = CubeState::solved();
+        for i in 1..=6 {
+            cube.apply_all(&sexy);
+            assert_eq!(cube.is_solved(), i == 6);
+        }
+    }
+
+    #[test]
+    fn rotation_is_all_layers() {
+        let mut cube = CubeState::solved();
+        cube.apply_all(&notation::parse_moves("R M' L' x'").unwrap());
+        assert_eq!(cube, CubeState::solved());
+        cube.apply_all(&notation::parse_moves("Rw M R' y E U' D z' S F B'").unwrap());
+        assert_eq!(cube, CubeState::solved());
+    }
+
+    #[test]
+    fn fixing_centers_undoes_rotations() {
+        let mut cube = CubeState::solved();
+        cube.apply_all(&notation::parse_moves("x y2 z'").unwrap());
+        assert!(cube.is_solved());
+        assert_ne!(cube, CubeState::solved());
+        assert_eq!(cube.with_centers_fixed(), CubeState::solved());
+
+        let mut sliced = CubeState::solved();
+        sliced.apply_all(&notation::parse_moves("M2").unwrap());
+        let mut turned = CubeState::solved();
+        turned.apply_all(&notation::parse_moves("R2 L2").unwrap());
+        assert_eq!(sliced.with_centers_fixed(), turned);
+    }
+
+    #[test]
+    fn r_moves_front_column_up() {
+        let mut cube = CubeState::solved();
+        cube.apply(mv(Face::R, false, false));
+        let u = cube.face(Face::U);
+        assert_eq!([u[2], u[5], u[8]], [Face::F; 3]);
+        assert_eq!([u[0], u[3], u[6]], [Face::U; 3]);
+        let b = cube.face(Face::B);
+        assert_eq!([b[0], b[3], b[6]], [Face::U; 3]);
+        let d = cube.face(Face::D);
+        assert_eq!([d[2], d[5], d[8]], [Face::B; 3]);
+        assert!(cube.face(Face::R).iter().all(|v| *v == Face::R));
+    }
+}
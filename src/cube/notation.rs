@@ -0,0 +1,159 @@
+//! Reading move sequences written in WCA notation: face turns (`R`, `U'`,
+//! `F2`), wide turns (`Rw`, `r`), slices (`M`, `E`, `S`) and rotations (`x`,
+//! `y`, `z`). Moves may be separated by spaces or written back to back, and
+//! parentheses are ignored.
+
+use super::{Face, Layers, Move};
+use std::fmt::{self, Formatter};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    /// The token does not start with a move letter.
+    UnknownMove,
+    /// Anything after the move other than `2` and `'`, e.g. `R3` or `U''`.
+    BadAmount,
+    /// A `w` on a move that is already wide, a slice or a rotation.
+    BadWide,
+}
+
+/// Where and why a move sequence could not be read.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    /// Column of the first character of the offending token, counting from 1.
+    pub column: usize,
+    pub token: String,
+    pub kind: ParseErrorKind,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let reason = match self.kind {
+            ParseErrorKind::UnknownMove => "unknown move",
+            ParseErrorKind::BadAmount => "bad turn amount in",
+            ParseErrorKind::BadWide => "cannot widen",
+        };
+        write!(f, "{} `{}` at column {}", reason, self.token, self.column)?;
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn parse_moves(s: &str) -> Result<Vec<Move>, ParseError> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut moves = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i].is_whitespace() || chars[i] == '(' || chars[i] == ')' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        while i < chars.len() && is_suffix(chars[i]) {
+            i += 1;
+        }
+        let token: String = chars[start..i].iter().collect();
+        match parse_move(&token) {
+            Ok(v) => moves.push(v),
+            Err(kind) => {
+                return Err(ParseError {
+                    column: start + 1,
+                    token,
+                    kind,
+                })
+            }
+        }
+    }
+    Ok(moves)
+}
+
+fn is_suffix(c: char) -> bool {
+    c == 'w' || c == '\'' || c == '’' || c.is_ascii_digit()
+}
+
+/// Reads a single move such as `Rw2'`.
+pub fn parse_move(token: &str) -> Result<Move, ParseErrorKind> {
+    let first = token.chars().next().ok_or(ParseErrorKind::UnknownMove)?;
+    let (face, mut layers) = match first {
+        'U' => (Face::U, Layers::Outer),
+        'L' => (Face::L, Layers::Outer),
+        'F' => (Face::F, Layers::Outer),
+        'R' => (Face::R, Layers::Outer),
+        'B' => (Face::B, Layers::Outer),
+        'D' => (Face::D, Layers::Outer),
+        'u' => (Face::U, Layers::Wide),
+        'l' => (Face::L, Layers::Wide),
+        'f' => (Face::F, Layers::Wide),
+        'r' => (Face::R, Layers::Wide),
+        'b' => (Face::B, Layers::Wide),
+        'd' => (Face::D, Layers::Wide),
+        'M' => (Face::L, Layers::Slice),
+        'E' => (Face::D, Layers::Slice),
+        'S' => (Face::F, Layers::Slice),
+        'x' => (Face::R, Layers::Rotation),
+        'y' => (Face::U, Layers::Rotation),
+        'z' => (Face::F, Layers::Rotation),
+        _ => return Err(ParseErrorKind::UnknownMove),
+    };
+    let mut rest = &token[first.len_utf8()..];
+    if let Some(v) = rest.strip_prefix('w') {
+        if layers != Layers::Outer {
+            return Err(ParseErrorKind::BadWide);
+        }
+        layers = Layers::Wide;
+        rest = v;
+    }
+    let half_turn = match rest.strip_prefix('2') {
+        Some(v) => {
+            rest = v;
+            true
+        }
+        None => false,
+    };
+    let prime = match rest {
+        "" => false,
+        "'" | "’" => true,
+        _ => return Err(ParseErrorKind::BadAmount),
+    };
+    Ok(Move {
+        face,
+        layers,
+        prime: prime && !half_turn,
+        half_turn,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::moves_to_string;
+
+    #[test]
+    fn parses_all_move_kinds() {
+        let moves = parse_moves("R U' F2 Rw r2 M' E S2 x y' z2 B2'").unwrap();
+        assert_eq!(moves_to_string(&moves), "R U' F2 Rw Rw2 M' E S2 x y' z2 B2");
+    }
+
+    #[test]
+    fn parses_moves_without_spaces() {
+        let moves = parse_moves("(RUR'U')").unwrap();
+        assert_eq!(moves_to_string(&moves), "R U R' U'");
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        let err = parse_moves("R U Q2 F").unwrap_err();
+        assert_eq!(err.column, 5);
+        assert_eq!(err.token, "Q2");
+        assert_eq!(err.kind, ParseErrorKind::UnknownMove);
+
+        let err = parse_moves("R  U3").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (4, "U3"));
+        assert_eq!(err.kind, ParseErrorKind::BadAmount);
+
+        let err = parse_moves("RUMw").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (3, "Mw"));
+        assert_eq!(err.kind, ParseErrorKind::BadWide);
+    }
+}
@@ -0,0 +1,225 @@
+//! Cubes of any size, described sticker by sticker like `CubeState`, and
+//! their random-move scrambles.
+
+use super::{
+    face_index, facelet_depth, facelet_index, facelet_position, rotate_cw, CubeState, Face,
+    FACELET_ORDER,
+};
+use rand::{seq::SliceRandom, Rng};
+use std::fmt::{self, Formatter};
+
+/// A turn of the `width` outermost layers on the side of `face`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct NxnMove {
+    pub face: Face,
+    pub width: usize,
+    pub prime: bool,
+    pub half_turn: bool,
+}
+
+impl std::fmt::Display for NxnMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self.width {
+            1 => write!(f, "{}", self.face)?,
+            2 => write!(f, "{}w", self.face)?,
+            w => write!(f, "{}{}w", w, self.face)?,
+        }
+        let halfstr = match self.half_turn {
+            true => "2",
+            false => "",
+        };
+        let primestr = match self.prime {
+            true => "\'",
+            false => "",
+        };
+        write!(f, "{}{}", halfstr, primestr)?;
+        Ok(())
+    }
+}
+
+impl NxnMove {
+    fn quarter_turns(&self) -> usize {
+        match (self.half_turn, self.prime) {
+            (true, _) => 2,
+            (false, true) => 3,
+            (false, false) => 1,
+        }
+    }
+}
+
+/// An `n` layer cube, with the same sticker layout as `CubeState`: faces in
+/// `FACELET_ORDER`, each `n` rows of `n` stickers.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NxnState {
+    pub n: usize,
+    pub facelets: Vec<Face>,
+}
+
+impl NxnState {
+    pub fn solved(n: usize) -> Self {
+        let facelets = FACELET_ORDER
+            .iter()
+            .flat_map(|v| std::iter::repeat_n(*v, n * n))
+            .collect();
+        Self { n, facelets }
+    }
+
+    /// Stickers of one face, row by row.
+    pub fn face(&self, face: Face) -> &[Face] {
+        let size = self.n * self.n;
+        let i = face_index(face);
+        &self.facelets[i * size..(i + 1) * size]
+    }
+
+    pub fn apply(&mut self, mv: NxnMove) {
+        let n = self.n;
+        let (normal, _, _) = mv.face.axes();
+        // Cubies of the innermost turned layer sit at this depth
+        let min_depth = n as i32 + 1 - 2 * mv.width as i32;
+        for _ in 0..mv.quarter_turns() {
+            let old = self.facelets.clone();
+            for (i, v) in old.iter().enumerate() {
+                let p = facelet_position(n, i);
+                if facelet_depth(mv.face, p) >= min_depth {
+                    self.facelets[facelet_index(n, rotate_cw(p, normal))] = *v;
+                }
+            }
+        }
+    }
+
+    pub fn apply_all(&mut self, moves: &[NxnMove]) {
+        for mv in moves {
+            self.apply(*mv);
+        }
+    }
+}
+
+impl From<&CubeState> for NxnState {
+    fn from(state: &CubeState) -> Self {
+        Self {
+            n: 3,
+            facelets: state.facelets.to_vec(),
+        }
+    }
+}
+
+/// Axis a face turns around, shared with its opposite face.
+fn axis(face: Face) -> usize {
+    match face {
+        Face::U | Face::D => 0,
+        Face::R | Face::L => 1,
+        Face::F | Face::B => 2,
+        Face::None => unreachable!(),
+    }
+}
+
+/// Turns used in scrambles: every face up to the layer before the middle,
+/// and on even cubes the half-cube turns of U, R and F only, since turning
+/// the other half is the same up to a rotation.
+fn scramble_turns(n: usize) -> Vec<(Face, usize)> {
+    let mut turns = vec![];
+    for face in FACELET_ORDER {
+        for width in 1..=(n - 1) / 2 {
+            turns.push((face, width));
+        }
+    }
+    if n.is_multiple_of(2) {
+        for face in [Face::U, Face::R, Face::F] {
+            turns.push((face, n / 2));
+        }
+    }
+    turns
+}
+
+/// A random-move scramble for an `n` layer cube. Consecutive turns around
+/// the same axis always turn different layers, so nothing cancels.
+pub fn gen_scramble<R: Rng + ?Sized>(n: usize, length: usize, rng: &mut R) -> Vec<NxnMove> {
+    let turns = scramble_turns(n);
+    let mut moves: Vec<NxnMove> = vec![];
+    let mut same_axis: Vec<(Face, usize)> = vec![];
+    while moves.len() < length {
+        let (face, width) = *turns.choose(rng).unwrap();
+        match moves.last() {
+            Some(last) if axis(last.face) == axis(face) => {
+                if same_axis.contains(&(face, width)) {
+                    continue;
+                }
+            }
+            _ => same_axis.clear(),
+        }
+        same_axis.push((face, width));
+        let (prime, half_turn) = match rng.gen_range(0..3) {
+            0 => (false, false),
+            1 => (true, false),
+            _ => (false, true),
+        };
+        moves.push(NxnMove {
+            face,
+            width,
+            prime,
+            half_turn,
+        });
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::{Layers, Move};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn matches_3x3_model() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut cube = CubeState::solved();
+        let mut nxn = NxnState::solved(3);
+        for _ in 0..40 {
+            let mv: Move = rng.gen();
+            let width = *[1, 2].choose(&mut rng).unwrap();
+            cube.apply(Move {
+                layers: if width == 1 { Layers::Outer } else { Layers::Wide },
+                ..mv
+            });
+            nxn.apply(NxnMove {
+                face: mv.face,
+                width,
+                prime: mv.prime,
+                half_turn: mv.half_turn,
+            });
+        }
+        assert_eq!(NxnState::from(&cube), nxn);
+    }
+
+    #[test]
+    fn formats_wide_moves() {
+        let moves: Vec<String> = [(1, false, false), (2, true, false), (3, false, true)]
+            .iter()
+            .map(|(width, prime, half_turn)| {
+                NxnMove {
+                    face: Face::R,
+                    width: *width,
+                    prime: *prime,
+                    half_turn: *half_turn,
+                }
+                .to_string()
+            })
+            .collect();
+        assert_eq!(moves, ["R", "Rw'", "3Rw2"]);
+    }
+
+    #[test]
+    fn scrambles_use_size_appropriate_turns() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for n in 2..=7 {
+            let moves = gen_scramble(n, 100, &mut rng);
+            assert_eq!(moves.len(), 100);
+            for mv in moves {
+                assert!(mv.width <= n / 2);
+                if n % 2 == 0 && mv.width == n / 2 {
+                    assert!(matches!(mv.face, Face::U | Face::R | Face::F));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Pretty patterns: well-known ones and ones written as `name: moves`.
+
+use super::notation::{parse_moves, ParseError};
+use super::{CubeState, Move};
+
+/// Well-known patterns and the algorithms making them from a solved cube.
+pub const PATTERNS: [(&str, &str); 11] = [
+    (
+        "Superflip",
+        "U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2",
+    ),
+    ("Checkerboard", "M2 E2 S2"),
+    ("Cube in cube", "F L F U' R U F2 L2 U' L' B D' B' L2 U"),
+    (
+        "Cube in cube in cube",
+        "U' L' U' F' R2 B' R F U B2 U B' L U' F U R F'",
+    ),
+    ("Six spots", "U D' R L' F B' U D'"),
+    ("Four spots", "F2 B2 U D' R2 L2 U D'"),
+    ("Tetris", "L R F B U' D' L' R'"),
+    ("Anaconda", "L U B' U' R L' B R' F B' D R D' F'"),
+    ("Python", "F2 R' B' U R' L F' L F' B D' R B L2"),
+    ("Black mamba", "R D L F' R L' D R' U D' B U' R' D'"),
+    ("Green mamba", "R D R F R' F' B D R' U' B' U D2"),
+];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pattern {
+    pub name: String,
+    pub moves: Vec<Move>,
+}
+
+impl Pattern {
+    /// Reads `name: moves`, or just moves for a pattern called `default_name`.
+    pub fn parse(s: &str, default_name: &str) -> Result<Self, ParseError> {
+        let (name, moves) = match s.split_once(':') {
+            Some((name, moves)) if !name.trim().is_empty() => (name.trim(), moves),
+            Some((_, moves)) => (default_name, moves),
+            None => (default_name, s),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            moves: parse_moves(moves)?,
+        })
+    }
+
+    /// The well-known patterns.
+    pub fn builtin() -> Vec<Self> {
+        PATTERNS
+            .iter()
+            .map(|(name, moves)| Self {
+                name: name.to_string(),
+                moves: parse_moves(moves).expect("built-in patterns parse"),
+            })
+            .collect()
+    }
+
+    /// The cube the pattern makes from a solved one.
+    pub fn state(&self) -> CubeState {
+        let mut state = CubeState::solved();
+        state.apply_all(&self.moves);
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::{cubie::CubieCube, moves_to_string, Face, FACELET_ORDER};
+
+    fn pattern(name: &str) -> CubeState {
+        let builtin = Pattern::builtin();
+        builtin.iter().find(|v| v.name == name).unwrap().state()
+    }
+
+    #[test]
+    fn builtin_patterns_look_right() {
+        let superflip = CubieCube::from_state(&pattern("Superflip")).unwrap();
+        assert_eq!(superflip.eo, [1; 12]);
+        assert_eq!(superflip.ep, CubieCube::solved().ep);
+        assert_eq!(superflip.cp, CubieCube::solved().cp);
+        let checkerboard = pattern("Checkerboard");
+        for face in FACELET_ORDER {
+            let stickers = checkerboard.face(face);
+            let center = stickers[4];
+            for (i, v) in stickers.iter().enumerate() {
+                assert_eq!(*v == center, i % 2 == 0, "{}", face);
+            }
+        }
+        let spots = pattern("Six spots");
+        for face in FACELET_ORDER {
+            let stickers = spots.face(face);
+            assert!(stickers[4] == face);
+            assert!(stickers
+                .iter()
+                .enumerate()
+                .all(|(i, v)| i == 4 || *v != face));
+        }
+        assert_ne!(pattern("Cube in cube").face(Face::U)[0], Face::U);
+    }
+
+    #[test]
+    fn reads_named_and_unnamed_patterns() {
+        let named = Pattern::parse("Sexy: R U R' U'", "Custom").unwrap();
+        assert_eq!(named.name, "Sexy");
+        assert_eq!(moves_to_string(&named.moves), "R U R' U'");
+        assert_eq!(Pattern::parse("R U", "Custom 1").unwrap().name, "Custom 1");
+        assert_eq!(
+            Pattern::parse(" : R U", "Custom 1").unwrap().name,
+            "Custom 1"
+        );
+        assert!(Pattern::parse("Bad: R U3", "Custom").is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! Reconstructions of solves: the solution split into named steps, written
+//! as `cross: D R2 F; f2l1: U R U' R'; ...`. A step can say how long it
+//! took after its name, like `cross (1.52s): D R2 F`, to give its TPS.
+
+use super::notation::{parse_moves, ParseError};
+use super::sequence::Metric;
+use super::{moves_to_string, CubeState, Move};
+use std::fmt::{self, Formatter};
+
+/// Names given to steps written without one, in CFOP order.
+pub const CFOP_STEPS: [&str; 7] = ["Cross", "F2L 1", "F2L 2", "F2L 3", "F2L 4", "OLL", "PLL"];
+
+/// Moves are counted the way reconstructions usually are: slices count one
+/// and rotations nothing.
+pub const RECON_METRIC: Metric = Metric::Stm;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub name: String,
+    pub moves: Vec<Move>,
+    /// Seconds the step took, if the reconstruction says.
+    pub time: Option<f64>,
+}
+
+impl Step {
+    pub fn count(&self) -> usize {
+        RECON_METRIC.count(&self.moves)
+    }
+
+    /// Turns per second, for steps with a time.
+    pub fn tps(&self) -> Option<f64> {
+        self.time
+            .filter(|v| *v > 0.0)
+            .map(|v| self.count() as f64 / v)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reconstruction {
+    pub steps: Vec<Step>,
+}
+
+/// Why a reconstruction can't be read.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReconError {
+    /// Nothing but whitespace was written.
+    Empty,
+    /// The moves of this step are not in WCA notation.
+    Notation(String, ParseError),
+    /// This step's time in brackets isn't a number of seconds.
+    Time(String),
+}
+
+impl std::fmt::Display for ReconError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            ReconError::Empty => write!(f, "no moves")?,
+            ReconError::Notation(step, e) => write!(f, "{}: {}", step, e)?,
+            ReconError::Time(step) => write!(f, "{}: the time isn't a number of seconds", step)?,
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ReconError {}
+
+impl std::fmt::Display for Reconstruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let steps: Vec<String> = self
+            .steps
+            .iter()
+            .map(|v| match v.time {
+                Some(time) => format!("{} ({}s): {}", v.name, time, moves_to_string(&v.moves)),
+                None => format!("{}: {}", v.name, moves_to_string(&v.moves)),
+            })
+            .collect();
+        write!(f, "{}", steps.join("; "))?;
+        Ok(())
+    }
+}
+
+impl Reconstruction {
+    /// Reads steps separated by `;`, each `name: moves` or just moves, with
+    /// the seconds it took in brackets after the name if known. Unnamed
+    /// steps are named after the CFOP step in their place, and a solution
+    /// that isn't split at all is one step.
+    pub fn parse(s: &str) -> Result<Self, ReconError> {
+        let parts: Vec<&str> = s.split(';').filter(|v| !v.trim().is_empty()).collect();
+        if parts.is_empty() {
+            return Err(ReconError::Empty);
+        }
+        let mut steps = vec![];
+        for (i, part) in parts.iter().enumerate() {
+            let (head, moves) = part.split_once(':').unwrap_or(("", part));
+            let (name, time) = match head
+                .trim()
+                .strip_suffix(')')
+                .and_then(|v| v.rsplit_once('('))
+            {
+                Some((name, time)) => (name.trim(), Some(time.trim().trim_end_matches('s'))),
+                None => (head.trim(), None),
+            };
+            let name = match name {
+                "" => default_name(i, parts.len()),
+                v => v.to_string(),
+            };
+            let time = match time.map(|v| v.parse::<f64>()) {
+                Some(Ok(v)) if v.is_finite() && v >= 0.0 => Some(v),
+                Some(_) => return Err(ReconError::Time(name)),
+                None => None,
+            };
+            let moves = parse_moves(moves).map_err(|e| ReconError::Notation(name.clone(), e))?;
+            steps.push(Step { name, moves, time });
+        }
+        Ok(Self { steps })
+    }
+
+    /// The whole solution as a single step.
+    pub fn single(moves: Vec<Move>) -> Self {
+        Self {
+            steps: vec![Step {
+                name: "Solution".to_string(),
+                moves,
+                time: None,
+            }],
+        }
+    }
+
+    pub fn moves(&self) -> Vec<Move> {
+        self.steps.iter().flat_map(|v| v.moves.clone()).collect()
+    }
+
+    pub fn count(&self) -> usize {
+        RECON_METRIC.count(&self.moves())
+    }
+
+    /// Whether the solution solves the cube `scramble` leaves, in any
+    /// orientation.
+    pub fn solves(&self, scramble: &[Move]) -> bool {
+        let mut state = CubeState::solved();
+        state.apply_all(scramble);
+        state.apply_all(&self.moves());
+        state.is_solved()
+    }
+}
+
+fn default_name(i: usize, steps: usize) -> String {
+    match (steps, CFOP_STEPS.get(i)) {
+        (1, _) => "Solution".to_string(),
+        (_, Some(v)) => v.to_string(),
+        (_, None) => format!("Step {}", i + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_named_and_cfop_steps() {
+        let recon = Reconstruction::parse("x2: D R; U R U' R'; oll: F R U R' U' F'").unwrap();
+        let names: Vec<&str> = recon.steps.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, ["x2", "F2L 1", "oll"]);
+        assert_eq!(recon.steps[1].count(), 4);
+        assert_eq!(recon.count(), 12);
+        assert_eq!(Reconstruction::parse(&recon.to_string()), Ok(recon));
+        let single = Reconstruction::parse("R U M' x").unwrap();
+        assert_eq!(single.steps[0].name, "Solution");
+        assert_eq!(single.count(), 3);
+        assert_eq!(Reconstruction::parse(" ; "), Err(ReconError::Empty));
+        assert!(matches!(
+            Reconstruction::parse("cross: R; R3"),
+            Err(ReconError::Notation(v, _)) if v == "F2L 1"
+        ));
+    }
+
+    #[test]
+    fn reads_step_times() {
+        let recon =
+            Reconstruction::parse("cross (1s): D R2; (2.5): U R U' R'; oll: F R U R' U' F'")
+                .unwrap();
+        let times: Vec<Option<f64>> = recon.steps.iter().map(|v| v.time).collect();
+        assert_eq!(times, [Some(1.0), Some(2.5), None]);
+        assert_eq!(recon.steps[1].name, "F2L 1");
+        assert_eq!(recon.steps[0].tps(), Some(2.0));
+        assert_eq!(recon.steps[2].tps(), None);
+        assert_eq!(
+            recon.to_string(),
+            "cross (1s): D R2; F2L 1 (2.5s): U R U' R'; oll: F R U R' U' F'"
+        );
+        assert_eq!(Reconstruction::parse(&recon.to_string()), Ok(recon));
+        assert_eq!(
+            Reconstruction::parse("cross (fast): D R2"),
+            Err(ReconError::Time("cross".to_string()))
+        );
+    }
+
+    #[test]
+    fn checks_the_solution() {
+        let scramble = parse_moves("R U R' U' F2").unwrap();
+        let recon = Reconstruction::parse("cross: F2; f2l1: U R U' R'").unwrap();
+        assert!(recon.solves(&scramble));
+        let rotated = Reconstruction::parse("cross: F2 x; f2l1: B R B' R'").unwrap();
+        assert!(rotated.solves(&scramble));
+        assert!(!Reconstruction::parse("F2 U R").unwrap().solves(&scramble));
+    }
+}
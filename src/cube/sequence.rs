@@ -0,0 +1,162 @@
+//! Operations on move sequences: inverting, cancelling redundant moves and
+//! counting length in the usual metrics.
+
+use super::{Face, Layers, Move};
+use std::fmt::{self, Formatter};
+
+/// Ways of counting the length of a sequence.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Metric {
+    /// Half turn metric: any turn of a face or wide block counts 1, slices
+    /// count as the two face turns they stand for.
+    Htm,
+    /// Quarter turn metric: like HTM, but half turns count 2.
+    Qtm,
+    /// Slice turn metric: any turn of any block of layers counts 1.
+    Stm,
+    /// Execution turn metric: every move counts 1, rotations included.
+    Etm,
+    /// Outer block turn metric, used in fewest moves: like HTM, where
+    /// slices aren't allowed to begin with.
+    Obtm,
+}
+
+/// Metrics shown side by side. OBTM is left out as it only differs from
+/// HTM on sequences with slices.
+pub const METRICS: [Metric; 4] = [Metric::Htm, Metric::Qtm, Metric::Stm, Metric::Etm];
+
+impl std::fmt::Display for Metric {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let text = match self {
+            Metric::Htm => "HTM",
+            Metric::Qtm => "QTM",
+            Metric::Stm => "STM",
+            Metric::Etm => "ETM",
+            Metric::Obtm => "OBTM",
+        };
+        write!(f, "{}", text)?;
+        Ok(())
+    }
+}
+
+impl Metric {
+    /// Length of `moves` in this metric.
+    pub fn count(&self, moves: &[Move]) -> usize {
+        moves.iter().map(|v| self.cost(v)).sum()
+    }
+
+    fn cost(&self, mv: &Move) -> usize {
+        let quarters = match mv.half_turn {
+            true => 2,
+            false => 1,
+        };
+        match (self, mv.layers) {
+            (Metric::Etm, _) => 1,
+            (_, Layers::Rotation) => 0,
+            (Metric::Stm, _) => 1,
+            (Metric::Htm | Metric::Obtm, Layers::Slice) => 2,
+            (Metric::Htm | Metric::Obtm, _) => 1,
+            (Metric::Qtm, Layers::Slice) => 2 * quarters,
+            (Metric::Qtm, _) => quarters,
+        }
+    }
+}
+
+/// The sequence that undoes `moves`.
+pub fn invert(moves: &[Move]) -> Vec<Move> {
+    moves.iter().rev().map(|v| v.inverse()).collect()
+}
+
+/// Axis a move turns around: moves on the same axis commute.
+fn axis(face: Face) -> usize {
+    match face {
+        Face::U | Face::D => 0,
+        Face::R | Face::L => 1,
+        Face::F | Face::B => 2,
+        Face::None => unreachable!(),
+    }
+}
+
+/// The move turning the same layers as `mv` by `quarter_turns` clockwise
+/// quarter turns, if that does anything.
+fn with_quarter_turns(mv: Move, quarter_turns: usize) -> Option<Move> {
+    let (prime, half_turn) = match quarter_turns % 4 {
+        0 => return None,
+        1 => (false, false),
+        2 => (false, true),
+        _ => (true, false),
+    };
+    Some(Move {
+        prime,
+        half_turn,
+        ..mv
+    })
+}
+
+/// Cancels and merges moves: within each run of moves on the same axis,
+/// turns of the same layers are added up, so `R R` becomes `R2`, `R L R`
+/// becomes `R2 L` and `U R R' U'` disappears. No two moves of the result
+/// turn the same layers without a move on another axis in between.
+pub fn simplify(moves: &[Move]) -> Vec<Move> {
+    let mut out: Vec<Move> = vec![];
+    for mv in moves {
+        // Moves at the end of `out` that commute with `mv`
+        let run = out
+            .iter()
+            .rev()
+            .take_while(|v| axis(v.face) == axis(mv.face))
+            .count();
+        let start = out.len() - run;
+        let same = out[start..]
+            .iter()
+            .position(|v| v.face == mv.face && v.layers == mv.layers);
+        match same {
+            Some(i) => {
+                let total = out[start + i].quarter_turns() + mv.quarter_turns();
+                match with_quarter_turns(*mv, total) {
+                    Some(v) => out[start + i] = v,
+                    None => {
+                        out.remove(start + i);
+                    }
+                }
+            }
+            None => out.push(*mv),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::{moves_to_string, notation::parse_moves};
+
+    fn simplified(s: &str) -> String {
+        moves_to_string(&simplify(&parse_moves(s).unwrap()))
+    }
+
+    #[test]
+    fn merges_and_cancels() {
+        assert_eq!(simplified("R R"), "R2");
+        assert_eq!(simplified("R L R"), "R2 L");
+        assert_eq!(simplified("R U R' U2 U R"), "R U R' U' R");
+        assert_eq!(simplified("R U R' U' U R U' R'"), "");
+        assert_eq!(simplified("U R R' U'"), "");
+        assert_eq!(simplified("R2 R'"), "R");
+        assert_eq!(simplified("R Rw M x x'"), "R Rw M");
+        assert_eq!(simplified("U D U' F"), "D F");
+    }
+
+    #[test]
+    fn inverts_sequences() {
+        let moves = parse_moves("R U2 F' Rw M x2").unwrap();
+        assert_eq!(moves_to_string(&invert(&moves)), "x2 M' Rw' F U2 R'");
+    }
+
+    #[test]
+    fn counts_in_each_metric() {
+        let moves = parse_moves("R U2 M' x Rw2 E2").unwrap();
+        let counts: Vec<usize> = METRICS.iter().map(|v| v.count(&moves)).collect();
+        assert_eq!(counts, [7, 11, 5, 6]);
+    }
+}
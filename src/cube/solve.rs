@@ -0,0 +1,78 @@
+//! Solves done on the virtual cube, kept with every move made.
+
+use super::notation::parse_moves;
+use super::sequence::Metric;
+use super::{moves_to_string, Move};
+use std::fmt::{self, Formatter};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solve {
+    /// Seconds from the first move to the solved cube.
+    pub time: f64,
+    pub scramble: Vec<Move>,
+    pub moves: Vec<Move>,
+}
+
+impl Solve {
+    /// Moves made, every turn and rotation counting one.
+    pub fn count(&self) -> usize {
+        Metric::Etm.count(&self.moves)
+    }
+
+    /// Turns per second.
+    pub fn tps(&self) -> f64 {
+        match self.time > 0.0 {
+            true => self.count() as f64 / self.time,
+            false => 0.0,
+        }
+    }
+
+    /// Reads a line written by `Display`: the time, scramble and moves
+    /// separated by tabs. The count and TPS after them are worked out again.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split('\t');
+        let time = fields.next()?.parse().ok()?;
+        let scramble = parse_moves(fields.next()?).ok()?;
+        let moves = parse_moves(fields.next()?).ok()?;
+        Some(Self {
+            time,
+            scramble,
+            moves,
+        })
+    }
+}
+
+impl std::fmt::Display for Solve {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "{:.3}\t{}\t{}\t{}\t{:.2}",
+            self.time,
+            moves_to_string(&self.scramble),
+            moves_to_string(&self.moves),
+            self.count(),
+            self.tps()
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_back_written_solves() {
+        let solve = Solve {
+            time: 2.5,
+            scramble: parse_moves("R U R' U'").unwrap(),
+            moves: parse_moves("U R U' y y' R'").unwrap(),
+        };
+        assert_eq!(solve.count(), 6);
+        assert_eq!(solve.tps(), 2.4);
+        let line = solve.to_string();
+        assert_eq!(line, "2.500\tR U R' U'\tU R U' y y' R'\t6\t2.40");
+        assert_eq!(Solve::parse(&line), Some(solve));
+        assert_eq!(Solve::parse("2.5\tR U"), None);
+    }
+}
@@ -0,0 +1,334 @@
+//! Kociemba's two-phase algorithm.
+//!
+//! Phase 1 brings the cube into the subgroup <U, D, R2, L2, F2, B2>, where
+//! every piece is oriented and the E-slice edges are in the E slice. Phase 2
+//! solves the cube using only moves of that subgroup. Both phases are IDA*
+//! searches over small coordinates, pruned with distance tables built once
+//! on first use.
+
+use super::cubie::{index_move, move_cube, CubieCube, N_SLICE};
+use super::Move;
+use std::{
+    collections::VecDeque,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+const N_TWIST: usize = 2187;
+const N_FLIP: usize = 2048;
+const N_PERM8: usize = 40320;
+const N_SLICE_PERM: usize = 24;
+
+/// Indices (into the 18 face turns) of the moves that keep a cube in the
+/// phase 2 subgroup.
+const PHASE2_MOVES: [usize; 10] = [0, 1, 2, 4, 7, 9, 10, 11, 13, 16];
+
+struct Tables {
+    twist_move: Vec<u16>,
+    flip_move: Vec<u16>,
+    slice_move: Vec<u16>,
+    corner_perm_move: Vec<u16>,
+    ud_edge_perm_move: Vec<u16>,
+    slice_perm_move: Vec<u16>,
+    twist_slice_prune: Vec<u8>,
+    flip_slice_prune: Vec<u8>,
+    corner_slice_prune: Vec<u8>,
+    edge_slice_prune: Vec<u8>,
+    slice_goal: usize,
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let all: Vec<usize> = (0..18).collect();
+        let twist_move = move_table(N_TWIST, &all, CubieCube::set_twist, CubieCube::twist);
+        let flip_move = move_table(N_FLIP, &all, CubieCube::set_flip, CubieCube::flip);
+        let slice_move = move_table(N_SLICE, &all, CubieCube::set_slice, CubieCube::slice);
+        let corner_perm_move = move_table(
+            N_PERM8,
+            &PHASE2_MOVES,
+            CubieCube::set_corner_perm,
+            CubieCube::corner_perm,
+        );
+        let ud_edge_perm_move = move_table(
+            N_PERM8,
+            &PHASE2_MOVES,
+            CubieCube::set_ud_edge_perm,
+            CubieCube::ud_edge_perm,
+        );
+        let slice_perm_move = move_table(
+            N_SLICE_PERM,
+            &PHASE2_MOVES,
+            CubieCube::set_slice_perm,
+            CubieCube::slice_perm,
+        );
+
+        let slice_goal = CubieCube::solved().slice();
+        let twist_slice_prune = prune_table(&twist_move, &slice_move, N_SLICE, 18, slice_goal);
+        let flip_slice_prune = prune_table(&flip_move, &slice_move, N_SLICE, 18, slice_goal);
+        let corner_slice_prune =
+            prune_table(&corner_perm_move, &slice_perm_move, N_SLICE_PERM, 10, 0);
+        let edge_slice_prune =
+            prune_table(&ud_edge_perm_move, &slice_perm_move, N_SLICE_PERM, 10, 0);
+
+        Tables {
+            twist_move,
+            flip_move,
+            slice_move,
+            corner_perm_move,
+            ud_edge_perm_move,
+            slice_perm_move,
+            twist_slice_prune,
+            flip_slice_prune,
+            corner_slice_prune,
+            edge_slice_prune,
+            slice_goal,
+        }
+    })
+}
+
+/// For every value of a coordinate and every move in `moves`, the value of
+/// the coordinate after the move.
+fn move_table(
+    n: usize,
+    moves: &[usize],
+    set: fn(&mut CubieCube, usize),
+    get: fn(&CubieCube) -> usize,
+) -> Vec<u16> {
+    let mut table = vec![0; n * moves.len()];
+    for c in 0..n {
+        let mut cube = CubieCube::solved();
+        set(&mut cube, c);
+        for (k, m) in moves.iter().enumerate() {
+            let mut next = cube;
+            next.multiply(&move_cube(*m));
+            table[c * moves.len() + k] = get(&next) as u16;
+        }
+    }
+    table
+}
+
+/// Breadth-first distance to the goal for every pair of two coordinates,
+/// indexed by `a * n_b + b`. The goal is `a == 0` and `b == b_goal`.
+fn prune_table(
+    a_move: &[u16],
+    b_move: &[u16],
+    n_b: usize,
+    n_moves: usize,
+    b_goal: usize,
+) -> Vec<u8> {
+    let n_a = a_move.len() / n_moves;
+    let mut table = vec![u8::MAX; n_a * n_b];
+    let mut queue = VecDeque::new();
+    table[b_goal] = 0;
+    queue.push_back(b_goal);
+    while let Some(i) = queue.pop_front() {
+        let (a, b) = (i / n_b, i % n_b);
+        for m in 0..n_moves {
+            let next = a_move[a * n_moves + m] as usize * n_b + b_move[b * n_moves + m] as usize;
+            if table[next] == u8::MAX {
+                table[next] = table[i] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    table
+}
+
+/// Whether turning face `m / 3` right after face `last / 3` is pointless:
+/// same face twice, or opposite faces in the non-canonical order.
+fn redundant(last: Option<usize>, m: usize) -> bool {
+    match last {
+        Some(l) => {
+            let (lf, f) = (l / 3, m / 3);
+            lf == f || lf == f + 3
+        }
+        None => false,
+    }
+}
+
+struct Search<'a> {
+    t: &'a Tables,
+    cube: CubieCube,
+    max_length: usize,
+    moves: Vec<usize>,
+    deadline: Option<Instant>,
+    nodes: usize,
+    timed_out: bool,
+}
+
+impl<'a> Search<'a> {
+    fn phase1_bound(&self, twist: usize, flip: usize, slice: usize) -> u8 {
+        self.t.twist_slice_prune[twist * N_SLICE + slice]
+            .max(self.t.flip_slice_prune[flip * N_SLICE + slice])
+    }
+
+    fn phase2_bound(&self, corners: usize, edges: usize, slice: usize) -> u8 {
+        self.t.corner_slice_prune[corners * N_SLICE_PERM + slice]
+            .max(self.t.edge_slice_prune[edges * N_SLICE_PERM + slice])
+    }
+
+    /// Checks the clock every few thousand nodes.
+    fn out_of_time(&mut self) -> bool {
+        self.nodes += 1;
+        if !self.timed_out && self.nodes.is_multiple_of(4096) {
+            self.timed_out = self.deadline.map(|v| Instant::now() >= v).unwrap_or(false);
+        }
+        self.timed_out
+    }
+
+    fn phase1(&mut self, twist: usize, flip: usize, slice: usize, togo: usize) -> bool {
+        if self.out_of_time() {
+            return false;
+        }
+        if togo == 0 {
+            // A phase 1 ending in a phase 2 move would have been found with
+            // a shorter phase 1 already
+            let last_in_phase2 = self
+                .moves
+                .last()
+                .map(|m| PHASE2_MOVES.contains(m))
+                .unwrap_or(false);
+            return twist == 0
+                && flip == 0
+                && slice == self.t.slice_goal
+                && !last_in_phase2
+                && self.start_phase2();
+        }
+        for m in 0..18 {
+            if redundant(self.moves.last().copied(), m) {
+                continue;
+            }
+            let nt = self.t.twist_move[twist * 18 + m] as usize;
+            let nf = self.t.flip_move[flip * 18 + m] as usize;
+            let ns = self.t.slice_move[slice * 18 + m] as usize;
+            if self.phase1_bound(nt, nf, ns) as usize >= togo {
+                continue;
+            }
+            self.moves.push(m);
+            if self.phase1(nt, nf, ns, togo - 1) {
+                return true;
+            }
+            self.moves.pop();
+        }
+        false
+    }
+
+    fn start_phase2(&mut self) -> bool {
+        let mut cube = self.cube;
+        for m in &self.moves {
+            cube.multiply(&move_cube(*m));
+        }
+        let (corners, edges, slice) = (cube.corner_perm(), cube.ud_edge_perm(), cube.slice_perm());
+        let budget = self.max_length - self.moves.len();
+        let bound = self.phase2_bound(corners, edges, slice) as usize;
+        (bound..=budget).any(|depth| self.phase2(corners, edges, slice, depth))
+    }
+
+    fn phase2(&mut self, corners: usize, edges: usize, slice: usize, togo: usize) -> bool {
+        if self.out_of_time() {
+            return false;
+        }
+        if togo == 0 {
+            return corners == 0 && edges == 0 && slice == 0;
+        }
+        for (k, m) in PHASE2_MOVES.iter().enumerate() {
+            if redundant(self.moves.last().copied(), *m) {
+                continue;
+            }
+            let nc = self.t.corner_perm_move[corners * 10 + k] as usize;
+            let ne = self.t.ud_edge_perm_move[edges * 10 + k] as usize;
+            let ns = self.t.slice_perm_move[slice * 10 + k] as usize;
+            if self.phase2_bound(nc, ne, ns) as usize >= togo {
+                continue;
+            }
+            self.moves.push(*m);
+            if self.phase2(nc, ne, ns, togo - 1) {
+                return true;
+            }
+            self.moves.pop();
+        }
+        false
+    }
+}
+
+/// Finds a solution of at most `max_length` moves, if the two-phase search
+/// can find one.
+pub fn solve(cube: &CubieCube, max_length: usize) -> Option<Vec<Move>> {
+    search(cube, max_length, None)
+}
+
+/// Looks for shorter and shorter solutions until `timeout` runs out and
+/// returns the shortest one found.
+pub fn solve_near_optimal(cube: &CubieCube, timeout: Duration) -> Vec<Move> {
+    let deadline = Instant::now() + timeout;
+    let mut best = solve(cube, 22).expect("every cube has a 22 move solution");
+    while !best.is_empty() {
+        match search(cube, best.len() - 1, Some(deadline)) {
+            Some(v) => best = v,
+            None => break,
+        }
+    }
+    best
+}
+
+fn search(cube: &CubieCube, max_length: usize, deadline: Option<Instant>) -> Option<Vec<Move>> {
+    let mut search = Search {
+        t: tables(),
+        cube: *cube,
+        max_length,
+        moves: vec![],
+        deadline,
+        nodes: 0,
+        timed_out: false,
+    };
+    let (twist, flip, slice) = (cube.twist(), cube.flip(), cube.slice());
+    let bound = search.phase1_bound(twist, flip, slice) as usize;
+    for depth in bound..=max_length {
+        if search.phase1(twist, flip, slice, depth) {
+            return Some(search.moves.iter().map(|m| index_move(*m)).collect());
+        }
+        if search.timed_out {
+            break;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::{scramble_to, sequence::simplify, CubeState};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn move_cubes_match_kociemba() {
+        let r = move_cube(3);
+        assert_eq!(r.cp, [4, 1, 2, 0, 7, 5, 6, 3]);
+        assert_eq!(r.co, [2, 0, 0, 1, 1, 0, 0, 2]);
+        assert_eq!(r.ep, [8, 1, 2, 3, 11, 5, 6, 7, 4, 9, 10, 0]);
+        assert_eq!(r.eo, [0; 12]);
+        let f = move_cube(6);
+        assert_eq!(f.cp, [1, 5, 2, 3, 0, 4, 6, 7]);
+        assert_eq!(f.co, [1, 2, 0, 0, 2, 1, 0, 0]);
+        assert_eq!(f.ep, [0, 9, 2, 3, 4, 8, 6, 7, 1, 5, 10, 11]);
+        assert_eq!(f.eo, [0, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0]);
+    }
+
+    #[test]
+    fn scrambles_are_solved_within_limit() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..5 {
+            let scramble = scramble_to(&CubieCube::random(&mut rng));
+            assert!(scramble.len() <= 21);
+            assert_eq!(simplify(&scramble), scramble);
+            let mut state = CubeState::solved();
+            state.apply_all(&scramble);
+            let cube = CubieCube::from_state(&state).unwrap();
+            let solution = solve(&cube, 21).unwrap();
+            assert!(solution.len() <= 21);
+            state.apply_all(&solution);
+            assert!(state.is_solved());
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! Training scrambles for parts of a solve. Each subset picks a uniformly
+//! random cube among the states it allows, and the scramble is the inverse
+//! of a two-phase solution to it, like full scrambles.
+
+use super::cubie::{move_cube, parity, CubieCube};
+use super::{scramble_to, Face, Layers, Move};
+use rand::{seq::SliceRandom, Rng};
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::{self, Formatter},
+    sync::OnceLock,
+};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Subset {
+    /// Any cube.
+    Full,
+    /// F2L solved, any last layer.
+    LastLayer,
+    /// F2L solved, last layer permuted but not oriented.
+    Oll,
+    /// F2L solved, last layer oriented but not permuted.
+    Pll,
+    /// F2L solved and last layer edges oriented.
+    Zbll,
+    /// Only the D cross solved.
+    F2l,
+    /// Anything reachable with R and U turns.
+    TwoGen,
+    /// Roux last six edges: anything reachable with M and U turns.
+    Lse,
+    /// Anything reachable with R, U and M turns: Roux's second block and
+    /// last layer along with the last six edges.
+    Rum,
+}
+
+pub const SUBSETS: [Subset; 9] = [
+    Subset::Full,
+    Subset::LastLayer,
+    Subset::Oll,
+    Subset::Pll,
+    Subset::Zbll,
+    Subset::F2l,
+    Subset::TwoGen,
+    Subset::Lse,
+    Subset::Rum,
+];
+
+impl std::fmt::Display for Subset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let text = match self {
+            Subset::Full => "Full",
+            Subset::LastLayer => "LL",
+            Subset::Oll => "OLL",
+            Subset::Pll => "PLL",
+            Subset::Zbll => "ZBLL",
+            Subset::F2l => "F2L",
+            Subset::TwoGen => "<R,U>",
+            Subset::Lse => "LSE <M,U>",
+            Subset::Rum => "<R,U,M>",
+        };
+        write!(f, "{}", text)?;
+        Ok(())
+    }
+}
+
+/// Corner and edge positions of the U layer, and of the pieces R and U move.
+const U_CORNERS: [usize; 4] = [0, 1, 2, 3];
+const U_EDGES: [usize; 4] = [0, 1, 2, 3];
+const RU_CORNERS: [usize; 6] = [0, 1, 2, 3, 4, 7];
+const RU_EDGES: [usize; 7] = [0, 1, 2, 3, 4, 8, 11];
+const NOT_CROSS_EDGES: [usize; 8] = [0, 1, 2, 3, 8, 9, 10, 11];
+/// The edges M and U move: the U layer's, DF and DB.
+const LSE_EDGES: [usize; 6] = [0, 1, 2, 3, 5, 7];
+/// The edges R, U and M move.
+const RUM_EDGES: [usize; 9] = [0, 1, 2, 3, 4, 5, 7, 8, 11];
+
+impl Subset {
+    /// Short lowercase name, used to name the subset's session file.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Subset::Full => "full",
+            Subset::LastLayer => "ll",
+            Subset::Oll => "oll",
+            Subset::Pll => "pll",
+            Subset::Zbll => "zbll",
+            Subset::F2l => "f2l",
+            Subset::TwoGen => "2gen",
+            Subset::Lse => "lse",
+            Subset::Rum => "rum",
+        }
+    }
+
+    /// A uniformly random cube of the subset. LSE and <R,U,M> cubes have
+    /// the centers home, and their scrambles turn M to a random place
+    /// afterwards.
+    pub fn random_state<R: Rng + ?Sized>(&self, rng: &mut R) -> CubieCube {
+        match self {
+            Subset::Full => CubieCube::random(rng),
+            Subset::LastLayer => random_last_layer(rng, true, true, true),
+            Subset::Oll => random_last_layer(rng, true, true, false),
+            Subset::Pll => random_last_layer(rng, false, false, true),
+            Subset::Zbll => random_last_layer(rng, true, false, true),
+            Subset::F2l => {
+                let mut cube = CubieCube::solved();
+                cube.cp.shuffle(rng);
+                shuffle_positions(&mut cube.ep, &NOT_CROSS_EDGES, rng);
+                if parity(&cube.cp) != parity(&cube.ep) {
+                    cube.ep.swap(8, 9);
+                }
+                cube.set_twist(rng.gen_range(0..2187));
+                randomize_orientation(&mut cube.eo, &NOT_CROSS_EDGES, 2, rng);
+                cube
+            }
+            Subset::TwoGen => {
+                let mut cube = CubieCube::solved();
+                cube.cp = *two_gen_corners().choose(rng).unwrap();
+                shuffle_positions(&mut cube.ep, &RU_EDGES, rng);
+                if parity(&cube.cp) != parity(&cube.ep) {
+                    cube.ep.swap(0, 1);
+                }
+                randomize_orientation(&mut cube.co, &RU_CORNERS, 3, rng);
+                cube
+            }
+            Subset::Lse => {
+                // With the centers home, M and U leave the L and R blocks
+                // solved and the U corners turned together, and can put the
+                // six other edges anywhere their parity allows
+                let mut cube = random_auf(rng);
+                shuffle_positions(&mut cube.ep, &LSE_EDGES, rng);
+                if parity(&cube.cp) != parity(&cube.ep) {
+                    cube.ep.swap(5, 7);
+                }
+                randomize_orientation(&mut cube.eo, &LSE_EDGES, 2, rng);
+                cube
+            }
+            Subset::Rum => {
+                // M moves no corners, so they are arranged as R and U
+                // allow, and M lets the nine edges go anywhere with the
+                // corners' parity
+                let mut cube = CubieCube::solved();
+                cube.cp = *two_gen_corners().choose(rng).unwrap();
+                randomize_orientation(&mut cube.co, &RU_CORNERS, 3, rng);
+                shuffle_positions(&mut cube.ep, &RUM_EDGES, rng);
+                if parity(&cube.cp) != parity(&cube.ep) {
+                    cube.ep.swap(0, 1);
+                }
+                randomize_orientation(&mut cube.eo, &RUM_EDGES, 2, rng);
+                cube
+            }
+        }
+    }
+
+    /// A scramble reaching a random cube of the subset. LSE and <R,U,M>
+    /// scrambles end with M turns taking the centers to any of their four
+    /// places, so the first block stays where it is.
+    pub fn gen_scramble<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<Move> {
+        let mut moves = scramble_to(&self.random_state(rng));
+        if matches!(self, Subset::Lse | Subset::Rum) {
+            let (prime, half_turn) = match rng.gen_range(0..4) {
+                0 => return moves,
+                1 => (false, false),
+                2 => (false, true),
+                _ => (true, false),
+            };
+            moves.push(Move {
+                face: Face::L,
+                layers: Layers::Slice,
+                prime,
+                half_turn,
+            });
+        }
+        moves
+    }
+}
+
+/// Solved F2L with a random last layer: corner and edge orientations are
+/// random if `orient_corners`/`orient_edges`, the permutation if `permute`.
+/// A random U turn on top, or none, keeps the AUF random either way.
+fn random_last_layer<R: Rng + ?Sized>(
+    rng: &mut R,
+    orient_corners: bool,
+    orient_edges: bool,
+    permute: bool,
+) -> CubieCube {
+    let mut cube = CubieCube::solved();
+    if permute {
+        shuffle_positions(&mut cube.cp, &U_CORNERS, rng);
+        shuffle_positions(&mut cube.ep, &U_EDGES, rng);
+        if parity(&cube.cp) != parity(&cube.ep) {
+            cube.ep.swap(0, 1);
+        }
+    }
+    if orient_corners {
+        randomize_orientation(&mut cube.co, &U_CORNERS, 3, rng);
+    }
+    if orient_edges {
+        randomize_orientation(&mut cube.eo, &U_EDGES, 2, rng);
+    }
+    cube.multiply(&random_auf(rng));
+    cube
+}
+
+/// A solved cube with the U layer turned one of its four ways.
+fn random_auf<R: Rng + ?Sized>(rng: &mut R) -> CubieCube {
+    match rng.gen_range(0..4) {
+        3 => CubieCube::solved(),
+        m => move_cube(m),
+    }
+}
+
+/// Shuffles the pieces at `positions` among themselves.
+fn shuffle_positions<R: Rng + ?Sized>(pieces: &mut [u8], positions: &[usize], rng: &mut R) {
+    let mut chosen: Vec<u8> = positions.iter().map(|v| pieces[*v]).collect();
+    chosen.shuffle(rng);
+    for (i, v) in positions.iter().zip(chosen) {
+        pieces[*i] = v;
+    }
+}
+
+/// Gives the pieces at `positions` random orientations out of `n` that add
+/// up to a multiple of `n`.
+fn randomize_orientation<R: Rng + ?Sized>(
+    orientations: &mut [u8],
+    positions: &[usize],
+    n: u8,
+    rng: &mut R,
+) {
+    let (last, rest) = positions.split_last().unwrap();
+    let mut sum = 0;
+    for i in rest {
+        orientations[*i] = rng.gen_range(0..n);
+        sum += orientations[*i];
+    }
+    orientations[*last] = (n - sum % n) % n;
+}
+
+/// The corner permutations R and U turns can reach. Only 120 of the 720
+/// arrangements of the six corners they move are possible.
+fn two_gen_corners() -> &'static Vec<[u8; 8]> {
+    static CORNERS: OnceLock<Vec<[u8; 8]>> = OnceLock::new();
+    CORNERS.get_or_init(|| {
+        let solved = CubieCube::solved().cp;
+        let mut seen = HashSet::from([solved]);
+        let mut queue = VecDeque::from([solved]);
+        while let Some(cp) = queue.pop_front() {
+            for m in [0, 3] {
+                let mut cube = CubieCube::solved();
+                cube.cp = cp;
+                cube.multiply(&move_cube(m));
+                if seen.insert(cube.cp) {
+                    queue.push_back(cube.cp);
+                }
+            }
+        }
+        let mut corners: Vec<[u8; 8]> = seen.into_iter().collect();
+        corners.sort();
+        corners
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::CubeState;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn f2l_solved(cube: &CubieCube) -> bool {
+        (4..8).all(|i| cube.cp[i] == i as u8 && cube.co[i] == 0)
+            && (4..12).all(|i| cube.ep[i] == i as u8 && cube.eo[i] == 0)
+    }
+
+    #[test]
+    fn states_belong_to_their_subset() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..50 {
+            let ll = Subset::LastLayer.random_state(&mut rng);
+            assert!(f2l_solved(&ll));
+            let oll = Subset::Oll.random_state(&mut rng);
+            assert!(f2l_solved(&oll));
+            let pll = Subset::Pll.random_state(&mut rng);
+            assert!(f2l_solved(&pll) && pll.co == [0; 8] && pll.eo == [0; 12]);
+            let zbll = Subset::Zbll.random_state(&mut rng);
+            assert!(f2l_solved(&zbll) && zbll.eo == [0; 12]);
+            let f2l = Subset::F2l.random_state(&mut rng);
+            assert!((4..8).all(|i| f2l.ep[i] == i as u8 && f2l.eo[i] == 0));
+            let ru = Subset::TwoGen.random_state(&mut rng);
+            assert!(ru.eo == [0; 12] && [5, 6].iter().all(|i| ru.cp[*i] == *i as u8));
+            assert!([5, 6, 7, 9, 10].iter().all(|i| ru.ep[*i] == *i as u8));
+            let rum = Subset::Rum.random_state(&mut rng);
+            assert!(two_gen_corners().contains(&rum.cp));
+            assert!([5, 6].iter().all(|i| rum.co[*i] == 0));
+            assert!([6, 9, 10].iter().all(|i| rum.ep[*i] == *i as u8 && rum.eo[*i] == 0));
+            for cube in [ll, oll, pll, zbll, f2l, ru, rum] {
+                assert_eq!(cube.co.iter().sum::<u8>() % 3, 0);
+                assert_eq!(cube.eo.iter().sum::<u8>() % 2, 0);
+                assert_eq!(parity(&cube.cp), parity(&cube.ep));
+            }
+        }
+    }
+
+    #[test]
+    fn all_four_aufs_occur() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let aufs: HashSet<[u8; 8]> = (0..40)
+            .map(|_| Subset::Oll.random_state(&mut rng).cp)
+            .collect();
+        assert_eq!(aufs.len(), 4);
+    }
+
+    #[test]
+    fn lse_keeps_the_blocks_and_reaches_both_corner_parities() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let solved = CubeState::solved();
+        let mut parities = HashSet::new();
+        for _ in 0..30 {
+            let mut state = CubeState::solved();
+            state.apply_all(&Subset::Lse.gen_scramble(&mut rng));
+            for face in [Face::L, Face::R] {
+                assert_eq!(state.face(face)[3..], solved.face(face)[3..]);
+            }
+            if state.face(Face::F)[4] == Face::F {
+                parities.insert(parity(&CubieCube::from_state(&state).unwrap().cp));
+            }
+        }
+        assert_eq!(parities.len(), 2);
+    }
+
+    #[test]
+    fn only_some_corner_permutations_are_two_gen() {
+        assert_eq!(two_gen_corners().len(), 120);
+    }
+
+    #[test]
+    fn scrambles_reach_the_state() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for subset in SUBSETS {
+            let cube = subset.random_state(&mut rng);
+            let mut state = CubeState::solved();
+            state.apply_all(&scramble_to(&cube));
+            assert_eq!(CubieCube::from_state(&state), Some(cube), "{}", subset);
+        }
+    }
+}
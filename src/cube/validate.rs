@@ -0,0 +1,198 @@
+//! Checking that a cube state entered by hand can be reached by turning the
+//! faces of a real cube, and saying what is wrong with it if not.
+
+use super::cubie::{corner_at, edge_at, parity, CubieCube};
+use super::{face_index, CubeState, Face, FACELET_ORDER};
+use std::fmt::{self, Formatter};
+
+pub const CORNER_NAMES: [&str; 8] = ["URF", "UFL", "ULB", "UBR", "DFR", "DLF", "DBL", "DRB"];
+pub const EDGE_NAMES: [&str; 12] = [
+    "UR", "UF", "UL", "UB", "DR", "DF", "DL", "DB", "FR", "FL", "BL", "BR",
+];
+
+/// Why a cube state cannot be solved, or a facelet string not read.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InvalidState {
+    /// A facelet string with this many stickers instead of 54.
+    Length(usize),
+    /// A character in a facelet string that is not a face letter.
+    UnknownColor(char),
+    /// A color with this many stickers instead of nine.
+    ColorCount(Face, usize),
+    /// Two centers of the same color.
+    Centers,
+    /// The stickers at this corner position don't make up a real corner.
+    InvalidCorner(usize),
+    /// The stickers at this edge position don't make up a real edge.
+    InvalidEdge(usize),
+    /// This corner piece appears more than once.
+    DuplicateCorner(usize),
+    /// This edge piece appears more than once.
+    DuplicateEdge(usize),
+    /// The corner twists add up to this many clockwise twists instead of a
+    /// multiple of three.
+    Twist(u8),
+    /// An odd number of edges are flipped.
+    Flip,
+    /// The corner and edge permutations have different parities.
+    Parity,
+}
+
+impl std::fmt::Display for InvalidState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            InvalidState::Length(n) => write!(f, "{} stickers instead of 54", n)?,
+            InvalidState::UnknownColor(c) => write!(f, "`{}` is not one of URFDLB", c)?,
+            InvalidState::ColorCount(face, n) => write!(f, "{} {} stickers instead of 9", n, face)?,
+            InvalidState::Centers => write!(f, "two centers have the same color")?,
+            InvalidState::InvalidCorner(i) => {
+                write!(f, "the {} corner has impossible colors", CORNER_NAMES[*i])?
+            }
+            InvalidState::InvalidEdge(i) => {
+                write!(f, "the {} edge has impossible colors", EDGE_NAMES[*i])?
+            }
+            InvalidState::DuplicateCorner(i) => {
+                write!(f, "the {} corner appears twice", CORNER_NAMES[*i])?
+            }
+            InvalidState::DuplicateEdge(i) => {
+                write!(f, "the {} edge appears twice", EDGE_NAMES[*i])?
+            }
+            InvalidState::Twist(1) => write!(f, "a corner is twisted clockwise")?,
+            InvalidState::Twist(_) => write!(f, "a corner is twisted anticlockwise")?,
+            InvalidState::Flip => write!(f, "an edge is flipped")?,
+            InvalidState::Parity => write!(f, "two pieces are swapped (parity)")?,
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for InvalidState {}
+
+/// Checks a cube held in any orientation and returns its pieces, seen with
+/// the centers in their standard places.
+pub fn validate(state: &CubeState) -> Result<CubieCube, InvalidState> {
+    for face in FACELET_ORDER {
+        let count = state.facelets.iter().filter(|v| **v == face).count();
+        if count != 9 {
+            return Err(InvalidState::ColorCount(face, count));
+        }
+    }
+    let mut centers: Vec<usize> = FACELET_ORDER
+        .iter()
+        .map(|v| face_index(state.face(*v)[4]))
+        .collect();
+    centers.sort();
+    centers.dedup();
+    if centers.len() != 6 {
+        return Err(InvalidState::Centers);
+    }
+
+    let state = state.with_centers_fixed();
+    let mut cube = CubieCube::solved();
+    for i in 0..8 {
+        (cube.cp[i], cube.co[i]) = corner_at(&state, i).ok_or(InvalidState::InvalidCorner(i))?;
+    }
+    for i in 0..12 {
+        (cube.ep[i], cube.eo[i]) = edge_at(&state, i).ok_or(InvalidState::InvalidEdge(i))?;
+    }
+    for piece in 0..8 {
+        if cube.cp.iter().filter(|v| **v == piece).count() > 1 {
+            return Err(InvalidState::DuplicateCorner(piece as usize));
+        }
+    }
+    for piece in 0..12 {
+        if cube.ep.iter().filter(|v| **v == piece).count() > 1 {
+            return Err(InvalidState::DuplicateEdge(piece as usize));
+        }
+    }
+
+    let twist = cube.co.iter().sum::<u8>() % 3;
+    if twist != 0 {
+        return Err(InvalidState::Twist(twist));
+    }
+    if cube.eo.iter().sum::<u8>() % 2 != 0 {
+        return Err(InvalidState::Flip);
+    }
+    if parity(&cube.cp) != parity(&cube.ep) {
+        return Err(InvalidState::Parity);
+    }
+    Ok(cube)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::{
+        cubie::{CORNER_FACELETS, EDGE_FACELETS},
+        notation::parse_moves,
+    };
+
+    fn scrambled() -> CubeState {
+        let mut state = CubeState::solved();
+        state.apply_all(&parse_moves("R U2 F' L D B2 R' U F2 D' L2 B U' R2 F").unwrap());
+        state
+    }
+
+    #[test]
+    fn accepts_real_cubes_in_any_orientation() {
+        let mut state = scrambled();
+        let cube = validate(&state).unwrap();
+        assert_eq!(Some(cube), CubieCube::from_state(&state));
+        state.apply_all(&parse_moves("x y2 M").unwrap());
+        assert!(validate(&state).is_ok());
+    }
+
+    #[test]
+    fn reads_facelet_strings() {
+        let solved = "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB";
+        assert_eq!(
+            CubeState::from_facelet_string(solved),
+            Ok(CubeState::solved())
+        );
+        assert_eq!(
+            CubeState::from_facelet_string(&solved[1..]),
+            Err(InvalidState::Length(53))
+        );
+        assert_eq!(
+            CubeState::from_facelet_string(&solved.replace("RRRR", "RRRX")),
+            Err(InvalidState::UnknownColor('X'))
+        );
+    }
+
+    #[test]
+    fn reports_each_problem() {
+        let mut state = CubeState::solved();
+        state.facelets[0] = Face::R;
+        assert_eq!(validate(&state), Err(InvalidState::ColorCount(Face::U, 8)));
+
+        let mut state = CubeState::solved();
+        (state.facelets[4], state.facelets[9]) = (Face::R, Face::U);
+        assert_eq!(validate(&state), Err(InvalidState::Centers));
+
+        // Swapping stickers between pieces keeps the counts right
+        let mut state = CubeState::solved();
+        state.facelets.swap(8, 29);
+        assert_eq!(validate(&state), Err(InvalidState::InvalidCorner(0)));
+
+        let mut state = CubeState::solved();
+        state.facelets.swap(10, 28);
+        assert_eq!(validate(&state), Err(InvalidState::InvalidEdge(0)));
+
+        let mut state = scrambled();
+        let [a, b, c] = CORNER_FACELETS[0];
+        (state.facelets[a], state.facelets[b], state.facelets[c]) =
+            (state.facelets[c], state.facelets[a], state.facelets[b]);
+        assert!(matches!(validate(&state), Err(InvalidState::Twist(_))));
+
+        let mut state = scrambled();
+        let [a, b] = EDGE_FACELETS[3];
+        state.facelets.swap(a, b);
+        assert_eq!(validate(&state), Err(InvalidState::Flip));
+
+        let mut state = scrambled();
+        for (a, b) in EDGE_FACELETS[0].iter().zip(EDGE_FACELETS[1]) {
+            state.facelets.swap(*a, b);
+        }
+        assert_eq!(validate(&state), Err(InvalidState::Parity));
+    }
+}
@@ -1,6 +1,7 @@
 mod ui;
 mod app;
 mod cube;
+mod puzzle;
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
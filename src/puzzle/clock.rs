@@ -0,0 +1,42 @@
+//! Clock scrambles in the WCA notation: a set of pins is pushed up, a wheel
+//! next to them is turned by some hours, and halfway through the puzzle is
+//! flipped over with `y2`.
+
+use rand::Rng;
+use std::fmt::{self, Formatter};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ClockMove {
+    /// Pins that are up, named by the corners of the puzzle, and how many
+    /// hours to turn a wheel clockwise (negative for anticlockwise).
+    Turn { pins: &'static str, hours: i32 },
+    /// Turn the puzzle over to the back.
+    Flip,
+}
+
+impl std::fmt::Display for ClockMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            ClockMove::Turn { pins, hours } if *hours >= 0 => write!(f, "{}{}+", pins, hours)?,
+            ClockMove::Turn { pins, hours } => write!(f, "{}{}-", pins, -hours)?,
+            ClockMove::Flip => write!(f, "y2")?,
+        }
+        Ok(())
+    }
+}
+
+const FRONT_PINS: [&str; 9] = ["UR", "DR", "DL", "UL", "U", "R", "D", "L", "ALL"];
+const BACK_PINS: [&str; 5] = ["U", "R", "D", "L", "ALL"];
+
+/// A random turn of every pin set. Every clock state is reachable this way,
+/// so this is already a random-state scramble.
+pub fn gen_scramble<R: Rng + ?Sized>(rng: &mut R) -> Vec<ClockMove> {
+    let mut turn = |pins| ClockMove::Turn {
+        pins,
+        hours: rng.gen_range(-5..=6),
+    };
+    let mut moves: Vec<ClockMove> = FRONT_PINS.iter().map(|v| turn(v)).collect();
+    moves.push(ClockMove::Flip);
+    moves.extend(BACK_PINS.iter().map(|v| turn(v)));
+    moves
+}
@@ -0,0 +1,78 @@
+//! Daily scramble sets: a few scrambles per puzzle that depend only on the
+//! date, so people can do the same scrambles without sharing them.
+
+use std::{
+    fmt::{self, Formatter},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Number of scrambles in a daily set.
+pub const DAILY_SCRAMBLES: usize = 5;
+
+/// A calendar day in UTC, counted from 1970-01-01.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Day(pub u64);
+
+impl Day {
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|v| v.as_secs())
+            .unwrap_or(0);
+        Self(secs / 86400)
+    }
+}
+
+impl std::fmt::Display for Day {
+    /// Formats the day as `YYYY-MM-DD`, using Howard Hinnant's
+    /// days-to-civil algorithm.
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let z = self.0 as i64 + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let d = doy - (153 * mp + 2) / 5 + 1;
+        let m = if mp < 10 { mp + 3 } else { mp - 9 };
+        let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+        write!(f, "{:04}-{:02}-{:02}", y, m, d)?;
+        Ok(())
+    }
+}
+
+/// Seed of scramble `i` of `puzzle`'s set on `day`: an FNV-1a hash of all
+/// three, which unlike `std`'s hashers is fixed forever.
+pub fn seed(day: Day, puzzle: &str, i: usize) -> u64 {
+    format!("{}/{}/{}", day, puzzle, i)
+        .bytes()
+        .fold(0xcbf29ce484222325, |acc, v| {
+            (acc ^ v as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(Day(0).to_string(), "1970-01-01");
+        assert_eq!(Day(11016).to_string(), "2000-02-29");
+        assert_eq!(Day(20742).to_string(), "2026-10-16");
+    }
+
+    #[test]
+    fn seeds_differ_by_day_puzzle_and_index() {
+        let seeds = [
+            seed(Day(20742), "3x3", 0),
+            seed(Day(20743), "3x3", 0),
+            seed(Day(20742), "4x4", 0),
+            seed(Day(20742), "3x3", 1),
+        ];
+        for (i, a) in seeds.iter().enumerate() {
+            assert!(seeds[i + 1..].iter().all(|b| a != b));
+        }
+        assert_eq!(seeds[0], seed(Day(20742), "3x3", 0));
+    }
+}
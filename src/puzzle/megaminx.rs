@@ -0,0 +1,325 @@
+//! The Megaminx, scrambled in Pochmann notation: `R++`/`R--` turn
+//! everything but the layer opposite R two fifths of a turn, `D++`/`D--`
+//! everything but the top layer, and each line ends with a turn of U. A
+//! model of its stickers shows where a scramble leaves them.
+
+use super::sticker::{cross, dot, turn, unit, Color, Point, Stickers};
+use rand::Rng;
+use std::f64::consts::PI;
+use std::fmt::{self, Formatter};
+use std::sync::OnceLock;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MegaminxMove {
+    R { plus: bool },
+    D { plus: bool },
+    U { prime: bool },
+}
+
+impl std::fmt::Display for MegaminxMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let s = match self {
+            MegaminxMove::R { plus: true } => "R++",
+            MegaminxMove::R { plus: false } => "R--",
+            MegaminxMove::D { plus: true } => "D++",
+            MegaminxMove::D { plus: false } => "D--",
+            MegaminxMove::U { prime: false } => "U",
+            MegaminxMove::U { prime: true } => "U'",
+        };
+        write!(f, "{}", s)?;
+        Ok(())
+    }
+}
+
+/// Stickers on each face: the center, then the edge next to each face
+/// around it and the corner after that edge, going clockwise.
+pub const FACE_STICKERS: usize = 11;
+
+/// Colors of the faces in sticker order: U, then F, R, BR, BL and L around
+/// it, D, then DR, DBR, B, DBL and DL around that.
+pub const FACES: [Color; 12] = [
+    Color::White,
+    Color::Green,
+    Color::Red,
+    Color::Blue,
+    Color::Yellow,
+    Color::Purple,
+    Color::Gray,
+    Color::LightYellow,
+    Color::Pink,
+    Color::LightGreen,
+    Color::Orange,
+    Color::LightBlue,
+];
+
+/// Height and spread of the faces around U and D, which are a fifth of the
+/// way round from their neighbours.
+const COS: f64 = 0.447_213_595_499_958;
+const SIN: f64 = 0.894_427_190_999_916;
+
+/// Distance from a face's center to the middle of its edges, with every
+/// face 1 from the middle of the puzzle.
+pub const EDGE_DISTANCE: f64 = (1.0 - COS) / SIN;
+
+/// How far along a face's direction the layer under it starts: the cuts
+/// cross each face around it halfway from its center to their edge.
+const CUT: f64 = (1.0 + COS) / 2.0;
+
+/// The direction face `f` faces. `x` points right, `y` up and `z` to the
+/// front, with F straight ahead.
+pub fn normal(f: usize) -> Point {
+    let (y, degrees) = match f {
+        0 => return [0.0, 1.0, 0.0],
+        6 => return [0.0, -1.0, 0.0],
+        1..=5 => (COS, 72.0 * (f - 1) as f64),
+        _ => (-COS, 36.0 + 72.0 * (f - 7) as f64),
+    };
+    let (sin, cos) = f64::to_radians(degrees).sin_cos();
+    [SIN * sin, y, SIN * cos]
+}
+
+/// The way from the center of face `f` to the middle of its edge with `g`.
+fn direction(f: usize, g: usize) -> Point {
+    let (n, m) = (normal(f), normal(g));
+    let height = dot(n, m);
+    unit([0, 1, 2].map(|i| m[i] - height * n[i]))
+}
+
+/// The five faces around face `f`, clockwise seen from outside, starting
+/// with the first in `FACES` order.
+pub fn neighbours(f: usize) -> [usize; 5] {
+    let n = normal(f);
+    let mut around: Vec<usize> = (0..FACES.len())
+        .filter(|g| *g != f && dot(normal(*g), n) > 0.0)
+        .collect();
+    let first = direction(f, around[0]);
+    let clockwise = cross(first, n);
+    let angle = |g: usize| {
+        let d = direction(f, g);
+        dot(d, clockwise).atan2(dot(d, first)).rem_euclid(2.0 * PI)
+    };
+    around.sort_by(|a, b| angle(*a).total_cmp(&angle(*b)));
+    [around[0], around[1], around[2], around[3], around[4]]
+}
+
+/// The point on the plane of face `f` that is `along` toward its edge with
+/// `toward` and `across` clockwise from there, seen from outside.
+pub fn face_point(f: usize, toward: usize, along: f64, across: f64) -> Point {
+    let n = normal(f);
+    let d = direction(f, toward);
+    let c = cross(d, n);
+    [0, 1, 2].map(|i| n[i] + along * d[i] + across * c[i])
+}
+
+/// The sticker of face `f` a point on its plane is on and how far it is
+/// from the nearest line between stickers, or `None` if it is off the face.
+pub fn sticker_at(f: usize, p: Point) -> Option<(usize, f64)> {
+    let heights = neighbours(f).map(|g| dot(p, normal(g)));
+    // Edges lie in the plane of the face on the other side
+    if heights.iter().any(|v| *v > 1.0) {
+        return None;
+    }
+    let line = heights
+        .iter()
+        .map(|v| (v - CUT).abs().min(1.0 - v) / SIN)
+        .fold(f64::MAX, f64::min);
+    let under: Vec<usize> = (0..5).filter(|k| heights[*k] > CUT).collect();
+    let sticker = match under[..] {
+        [] => 0,
+        [k] => 1 + 2 * k,
+        [0, 4] => 10,
+        [k, ..] => 2 + 2 * k,
+    };
+    Some((sticker, line))
+}
+
+struct Model {
+    solved: Stickers,
+    /// R++, R--, D++, D--, U and U'.
+    moves: Vec<Vec<usize>>,
+}
+
+fn model() -> &'static Model {
+    static MODEL: OnceLock<Model> = OnceLock::new();
+    MODEL.get_or_init(|| {
+        let mut points = vec![];
+        let mut colors = vec![];
+        for (f, color) in FACES.iter().enumerate() {
+            let first = neighbours(f)[0];
+            points.push(normal(f));
+            for k in 0..5 {
+                // The edge toward the `k`th face around, then the corner
+                // after it, both well inside the layers they are in
+                for (degrees, distance) in [
+                    (72.0 * k as f64, 0.8 * EDGE_DISTANCE),
+                    (
+                        72.0 * k as f64 + 36.0,
+                        0.8 * EDGE_DISTANCE / f64::cos(PI / 5.0),
+                    ),
+                ] {
+                    let (sin, cos) = f64::to_radians(degrees).sin_cos();
+                    points.push(face_point(f, first, distance * cos, distance * sin));
+                }
+            }
+            colors.extend([*color; FACE_STICKERS]);
+        }
+        let mut moves = vec![];
+        // R and D turn all but the opposite layer, U just its own
+        for (f, min_dot, angle) in [(2, -CUT, 0.8 * PI), (6, -CUT, 0.8 * PI), (0, CUT, 0.4 * PI)] {
+            for sign in [1.0, -1.0] {
+                moves.push(turn(&points, normal(f), min_dot, sign * angle));
+            }
+        }
+        Model {
+            solved: Stickers { colors },
+            moves,
+        }
+    })
+}
+
+fn move_index(mv: MegaminxMove) -> usize {
+    match mv {
+        MegaminxMove::R { plus } => !plus as usize,
+        MegaminxMove::D { plus } => 2 + !plus as usize,
+        MegaminxMove::U { prime } => 4 + prime as usize,
+    }
+}
+
+/// A Megaminx, as the color of each of its 132 stickers: faces in `FACES`
+/// order, each its center then its edges and corners. The puzzle is held
+/// still, so R and D moves carry most centers along.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MegaminxState {
+    pub stickers: Stickers,
+}
+
+impl MegaminxState {
+    pub fn solved() -> Self {
+        Self {
+            stickers: model().solved.clone(),
+        }
+    }
+
+    pub fn apply(&mut self, mv: MegaminxMove) {
+        self.stickers.apply(&model().moves[move_index(mv)]);
+    }
+
+    pub fn apply_all(&mut self, moves: &[MegaminxMove]) {
+        for mv in moves {
+            self.apply(*mv);
+        }
+    }
+
+    /// Stickers of face `f` of `FACES`: the center, then the edges and
+    /// corners.
+    pub fn face(&self, f: usize) -> &[Color] {
+        &self.stickers.colors[f * FACE_STICKERS..(f + 1) * FACE_STICKERS]
+    }
+}
+
+/// `lines` lines of ten alternating R and D moves, each line ending in U or
+/// U'.
+pub fn gen_scramble<R: Rng + ?Sized>(lines: usize, rng: &mut R) -> Vec<MegaminxMove> {
+    let mut moves = vec![];
+    for _ in 0..lines {
+        for i in 0..10 {
+            let plus = rng.gen();
+            moves.push(match i % 2 {
+                0 => MegaminxMove::R { plus },
+                _ => MegaminxMove::D { plus },
+            });
+        }
+        moves.push(MegaminxMove::U { prime: rng.gen() });
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOVES: [MegaminxMove; 3] = [
+        MegaminxMove::R { plus: true },
+        MegaminxMove::D { plus: true },
+        MegaminxMove::U { prime: false },
+    ];
+
+    #[test]
+    fn turns_come_back_around() {
+        let mut state = MegaminxState::solved();
+        for mv in MOVES {
+            state.apply(mv);
+            assert_ne!(state, MegaminxState::solved());
+            let back = match mv {
+                MegaminxMove::R { .. } => MegaminxMove::R { plus: false },
+                MegaminxMove::D { .. } => MegaminxMove::D { plus: false },
+                MegaminxMove::U { .. } => MegaminxMove::U { prime: true },
+            };
+            state.apply(back);
+            assert_eq!(state, MegaminxState::solved());
+            state.apply_all(&[mv; 5]);
+            assert_eq!(state, MegaminxState::solved());
+        }
+    }
+
+    #[test]
+    fn turns_move_their_layers() {
+        let moved = |mv| {
+            let perm = &model().moves[move_index(mv)];
+            perm.iter().enumerate().filter(|(i, v)| *i != **v).count()
+        };
+        // All but the turning face's center, and its neighbours' stickers
+        // next to it
+        assert_eq!(moved(MOVES[2]), FACE_STICKERS - 1 + 5 * 3);
+        assert_eq!(moved(MOVES[0]), 132 - FACE_STICKERS - 5 * 3 - 1);
+        assert_eq!(moved(MOVES[1]), 132 - FACE_STICKERS - 5 * 3 - 1);
+    }
+
+    #[test]
+    fn turns_go_clockwise() {
+        // R++ takes F up over U to BR, and D++ takes DR under DBR to B
+        let mut state = MegaminxState::solved();
+        state.apply(MOVES[0]);
+        assert_eq!(state.face(3)[0], Color::Green);
+        let mut state = MegaminxState::solved();
+        state.apply(MOVES[1]);
+        assert_eq!(state.face(9)[0], Color::LightYellow);
+        // U takes F's edge under it to L
+        let mut state = MegaminxState::solved();
+        state.apply(MOVES[2]);
+        let k = neighbours(5).iter().position(|v| *v == 0).unwrap();
+        assert_eq!(state.face(5)[1 + 2 * k], Color::Green);
+        assert_eq!(state.face(5)[0], Color::Purple);
+    }
+
+    #[test]
+    fn stickers_are_found_where_they_are() {
+        let points = |f: usize| {
+            let first = neighbours(f)[0];
+            (0..FACE_STICKERS).map(move |i| match i {
+                0 => face_point(f, first, 0.0, 0.0),
+                _ => {
+                    let degrees = 36.0 * (i - 1) as f64;
+                    let (sin, cos) = f64::to_radians(degrees).sin_cos();
+                    let distance = match i % 2 {
+                        1 => 0.8 * EDGE_DISTANCE,
+                        _ => EDGE_DISTANCE,
+                    };
+                    face_point(f, first, distance * cos, distance * sin)
+                }
+            })
+        };
+        for f in 0..FACES.len() {
+            // Faces are next to each other both ways round, never to
+            // themselves
+            assert!(!neighbours(f).contains(&f));
+            assert!(neighbours(f).iter().all(|g| neighbours(*g).contains(&f)));
+            for (i, p) in points(f).enumerate() {
+                assert_eq!(sticker_at(f, p).map(|v| v.0), Some(i));
+            }
+            // Beyond the middle of the first edge
+            let off = face_point(f, neighbours(f)[0], 1.1 * EDGE_DISTANCE, 0.0);
+            assert_eq!(sticker_at(f, off), None);
+        }
+    }
+}
@@ -0,0 +1,401 @@
+//! The puzzles cube-tui can scramble, and the scrambles and states they
+//! produce.
+
+pub mod clock;
+pub mod daily;
+pub mod megaminx;
+pub mod pyraminx;
+pub mod skewb;
+pub mod square1;
+pub mod sticker;
+
+use super::cube::{
+    bld, cube2,
+    filter::{FilterStats, ScrambleFilter},
+    filtered_scramble, fmc, moves_to_string,
+    nxn::{self, NxnMove, NxnState},
+    subset::{Subset, SUBSETS},
+    CubeState, Move,
+};
+use clock::ClockMove;
+use daily::Day;
+use megaminx::{MegaminxMove, MegaminxState};
+use pyraminx::{PyraminxMove, PyraminxState};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use skewb::{SkewbMove, SkewbState};
+use square1::{Square1, Square1Move};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Puzzle {
+    Cube2,
+    /// 3x3, scrambled to any state or to one of the training subsets.
+    Cube3(Subset),
+    /// 3x3 blindfolded: random state and random orientation.
+    Bld3,
+    /// 3x3 fewest moves, with results in moves instead of seconds.
+    Fmc,
+    Cube4,
+    Cube5,
+    Cube6,
+    Cube7,
+    Pyraminx,
+    Skewb,
+    Megaminx,
+    Square1,
+    Clock,
+}
+
+pub const PUZZLES: [Puzzle; 13] = [
+    Puzzle::Cube3(Subset::Full),
+    Puzzle::Bld3,
+    Puzzle::Fmc,
+    Puzzle::Cube2,
+    Puzzle::Cube4,
+    Puzzle::Cube5,
+    Puzzle::Cube6,
+    Puzzle::Cube7,
+    Puzzle::Pyraminx,
+    Puzzle::Skewb,
+    Puzzle::Megaminx,
+    Puzzle::Square1,
+    Puzzle::Clock,
+];
+
+impl std::fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Puzzle::Bld3 => write!(f, "3BLD")?,
+            Puzzle::Fmc => write!(f, "FMC")?,
+            Puzzle::Pyraminx => write!(f, "Pyraminx")?,
+            Puzzle::Skewb => write!(f, "Skewb")?,
+            Puzzle::Megaminx => write!(f, "Megaminx")?,
+            Puzzle::Square1 => write!(f, "Square-1")?,
+            Puzzle::Clock => write!(f, "Clock")?,
+            Puzzle::Cube3(Subset::Full) => write!(f, "3x3")?,
+            Puzzle::Cube3(subset) => write!(f, "3x3 {}", subset)?,
+            _ => {
+                let n = self.size();
+                write!(f, "{}x{}", n, n)?
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Puzzle {
+    /// Number of layers of the cube, 0 for the other puzzles.
+    pub fn size(&self) -> usize {
+        match self {
+            Puzzle::Cube2 => 2,
+            Puzzle::Cube3(_) | Puzzle::Bld3 | Puzzle::Fmc => 3,
+            Puzzle::Cube4 => 4,
+            Puzzle::Cube5 => 5,
+            Puzzle::Cube6 => 6,
+            Puzzle::Cube7 => 7,
+            _ => 0,
+        }
+    }
+
+    /// Short lowercase name, used to name the puzzle's session file.
+    pub fn id(&self) -> String {
+        match self {
+            Puzzle::Square1 => "sq1".to_string(),
+            Puzzle::Cube3(Subset::Full) => "3x3".to_string(),
+            Puzzle::Cube3(subset) => format!("3x3-{}", subset.id()),
+            _ => self.to_string().to_lowercase(),
+        }
+    }
+
+    /// A random scramble drawn from `rng`. The same RNG state always gives
+    /// the same scramble.
+    pub fn gen_scramble<R: Rng + ?Sized>(&self, rng: &mut R) -> Scramble {
+        match self {
+            Puzzle::Cube3(subset) => Scramble::Cube(subset.gen_scramble(rng)),
+            Puzzle::Bld3 => Scramble::Cube(bld::gen_scramble(rng)),
+            Puzzle::Fmc => Scramble::Cube(fmc::gen_scramble(rng)),
+            Puzzle::Cube2 => Scramble::Nxn(2, cube2::gen_scramble(rng)),
+            Puzzle::Cube4 => Scramble::Nxn(4, nxn::gen_scramble(4, 40, rng)),
+            Puzzle::Cube5 => Scramble::Nxn(5, nxn::gen_scramble(5, 60, rng)),
+            Puzzle::Cube6 => Scramble::Nxn(6, nxn::gen_scramble(6, 80, rng)),
+            Puzzle::Cube7 => Scramble::Nxn(7, nxn::gen_scramble(7, 100, rng)),
+            Puzzle::Pyraminx => Scramble::Pyraminx(pyraminx::gen_scramble(rng)),
+            Puzzle::Skewb => Scramble::Skewb(skewb::gen_scramble(rng)),
+            Puzzle::Megaminx => Scramble::Megaminx(megaminx::gen_scramble(7, rng)),
+            Puzzle::Square1 => Scramble::Square1(square1::gen_scramble(rng)),
+            Puzzle::Clock => Scramble::Clock(clock::gen_scramble(rng)),
+        }
+    }
+
+    /// Whether the scramble filter applies: only plain random state 3x3
+    /// scrambles are filtered.
+    pub fn filtered(&self) -> bool {
+        *self == Puzzle::Cube3(Subset::Full)
+    }
+
+    /// A random scramble that passes `filter`, for the puzzles it applies
+    /// to. Others are scrambled as usual.
+    pub fn filtered_scramble<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        filter: &ScrambleFilter,
+        stats: &mut FilterStats,
+    ) -> Scramble {
+        match self.filtered() {
+            true => Scramble::Cube(filtered_scramble(rng, filter, stats)),
+            false => self.gen_scramble(rng),
+        }
+    }
+
+    /// The same puzzle scrambled to the next training subset, for 3x3.
+    pub fn next_subset(&self) -> Self {
+        match self {
+            Puzzle::Cube3(subset) => {
+                let i = SUBSETS.iter().position(|v| v == subset).unwrap_or(0);
+                Puzzle::Cube3(SUBSETS[(i + 1) % SUBSETS.len()])
+            }
+            p => *p,
+        }
+    }
+
+    /// The scramble for `seed`, the same on every machine running this
+    /// version of cube-tui.
+    pub fn seeded_scramble(&self, seed: u64) -> Scramble {
+        self.gen_scramble(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Scramble `i` of the daily set for `day`, shared by everyone
+    /// scrambling this puzzle that day.
+    pub fn daily_scramble(&self, day: Day, i: usize) -> Scramble {
+        self.seeded_scramble(daily::seed(day, &self.id(), i))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Scramble {
+    Cube(Vec<Move>),
+    Nxn(usize, Vec<NxnMove>),
+    Pyraminx(Vec<PyraminxMove>),
+    Skewb(Vec<SkewbMove>),
+    Megaminx(Vec<MegaminxMove>),
+    Square1(Vec<Square1Move>),
+    Clock(Vec<ClockMove>),
+}
+
+fn join<T: Display>(moves: &[T]) -> String {
+    let moves: Vec<String> = moves.iter().map(|v| v.to_string()).collect();
+    moves.join(" ")
+}
+
+impl std::fmt::Display for Scramble {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Scramble::Cube(moves) => write!(f, "{}", moves_to_string(moves))?,
+            Scramble::Nxn(_, moves) => write!(f, "{}", join(moves))?,
+            Scramble::Pyraminx(moves) => write!(f, "{}", join(moves))?,
+            Scramble::Skewb(moves) => write!(f, "{}", join(moves))?,
+            Scramble::Clock(moves) => write!(f, "{}", join(moves))?,
+            // One line per U turn, as on WCA scramble sheets
+            Scramble::Megaminx(moves) => {
+                let lines: Vec<String> = moves
+                    .split_inclusive(|v| matches!(v, MegaminxMove::U { .. }))
+                    .map(join)
+                    .collect();
+                write!(f, "{}", lines.join("\n"))?
+            }
+            // Slices are written right after the twist before them
+            Scramble::Square1(moves) => {
+                let s: String = moves
+                    .iter()
+                    .map(|v| match v {
+                        Square1Move::Slice => "/ ".to_string(),
+                        _ => v.to_string(),
+                    })
+                    .collect();
+                write!(f, "{}", s.trim_end())?
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Scramble {
+    /// The state the scramble takes a solved puzzle to, for puzzles that
+    /// have a model.
+    pub fn state(&self) -> Option<PuzzleState> {
+        self.state_after(usize::MAX)
+    }
+
+    /// The state after the first `n` moves of the scramble.
+    pub fn state_after(&self, n: usize) -> Option<PuzzleState> {
+        match self {
+            Scramble::Cube(moves) => {
+                let mut state = CubeState::solved();
+                state.apply_all(&moves[..n.min(moves.len())]);
+                Some(PuzzleState::Cube(state))
+            }
+            Scramble::Nxn(size, moves) => {
+                let mut state = NxnState::solved(*size);
+                state.apply_all(&moves[..n.min(moves.len())]);
+                Some(PuzzleState::Nxn(state))
+            }
+            Scramble::Pyraminx(moves) => {
+                let mut state = PyraminxState::solved();
+                state.apply_all(&moves[..n.min(moves.len())]);
+                Some(PuzzleState::Pyraminx(state))
+            }
+            Scramble::Skewb(moves) => {
+                let mut state = SkewbState::solved();
+                state.apply_all(&moves[..n.min(moves.len())]);
+                Some(PuzzleState::Skewb(state))
+            }
+            Scramble::Megaminx(moves) => {
+                let mut state = MegaminxState::solved();
+                state.apply_all(&moves[..n.min(moves.len())]);
+                Some(PuzzleState::Megaminx(state))
+            }
+            Scramble::Square1(moves) => {
+                let mut state = Square1::solved();
+                for mv in &moves[..n.min(moves.len())] {
+                    state.apply(*mv);
+                }
+                Some(PuzzleState::Square1(state))
+            }
+            _ => None,
+        }
+    }
+
+    /// The moves one by one, for scrambles that can be stepped through.
+    pub fn steps(&self) -> Option<Vec<String>> {
+        match self {
+            Scramble::Cube(moves) => Some(moves.iter().map(|v| v.to_string()).collect()),
+            Scramble::Nxn(_, moves) => Some(moves.iter().map(|v| v.to_string()).collect()),
+            Scramble::Pyraminx(moves) => Some(moves.iter().map(|v| v.to_string()).collect()),
+            Scramble::Skewb(moves) => Some(moves.iter().map(|v| v.to_string()).collect()),
+            Scramble::Megaminx(moves) => Some(moves.iter().map(|v| v.to_string()).collect()),
+            Scramble::Square1(moves) => Some(moves.iter().map(|v| v.to_string()).collect()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum PuzzleState {
+    Cube(CubeState),
+    Nxn(NxnState),
+    Pyraminx(PyraminxState),
+    Skewb(SkewbState),
+    Megaminx(MegaminxState),
+    Square1(Square1),
+}
+
+impl PuzzleState {
+    /// The 3x3 state, which most tools work with.
+    pub fn cube(&self) -> Option<&CubeState> {
+        match self {
+            PuzzleState::Cube(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// The state of a cube other than 3x3.
+    pub fn nxn(&self) -> Option<&NxnState> {
+        match self {
+            PuzzleState::Nxn(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// An optimal solution, for the Pyraminx and Skewb, which are small
+    /// enough to solve outright.
+    pub fn optimal_solution(&self) -> Option<Vec<String>> {
+        let moves: Vec<String> = match self {
+            PuzzleState::Pyraminx(v) => pyraminx::solve(v)?.iter().map(|v| v.to_string()).collect(),
+            PuzzleState::Skewb(v) => skewb::solve(v)?.iter().map(|v| v.to_string()).collect(),
+            _ => return None,
+        };
+        Some(moves)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_scrambles_are_reproducible() {
+        for puzzle in PUZZLES {
+            let a = puzzle.seeded_scramble(42).to_string();
+            assert_eq!(a, puzzle.seeded_scramble(42).to_string());
+            assert_ne!(a, puzzle.seeded_scramble(43).to_string());
+        }
+    }
+
+    #[test]
+    fn daily_sets_depend_on_day_and_index() {
+        let puzzle = Puzzle::Cube3(Subset::Full);
+        let a = puzzle.daily_scramble(Day(20742), 0).to_string();
+        assert_eq!(a, puzzle.daily_scramble(Day(20742), 0).to_string());
+        assert_ne!(a, puzzle.daily_scramble(Day(20742), 1).to_string());
+        assert_ne!(a, puzzle.daily_scramble(Day(20743), 0).to_string());
+    }
+
+    #[test]
+    fn steps_through_scrambles() {
+        let scramble = Puzzle::Cube3(Subset::Full).seeded_scramble(1);
+        let steps = scramble.steps().unwrap();
+        assert_eq!(steps.join(" "), scramble.to_string());
+        let start = scramble.state_after(0).unwrap();
+        assert!(start.cube().unwrap().is_solved());
+        let end = scramble.state_after(steps.len()).unwrap();
+        assert_eq!(end.cube(), scramble.state().unwrap().cube());
+        assert!(Puzzle::Clock.seeded_scramble(1).steps().is_none());
+    }
+
+    #[test]
+    fn megaminx_scrambles_have_seven_lines() {
+        let text = Puzzle::Megaminx.seeded_scramble(1).to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 7);
+        for line in lines {
+            let moves: Vec<&str> = line.split(' ').collect();
+            assert_eq!(moves.len(), 11);
+            assert!(moves[0].starts_with('R') && moves[9].starts_with('D'));
+            assert!(moves[10] == "U" || moves[10] == "U'");
+        }
+    }
+
+    #[test]
+    fn formats_square1_and_clock() {
+        let moves = [
+            Square1Move::Twist(1, 0),
+            Square1Move::Slice,
+            Square1Move::Twist(-3, 3),
+            Square1Move::Slice,
+        ];
+        assert_eq!(
+            Scramble::Square1(moves.to_vec()).to_string(),
+            "(1,0)/ (-3,3)/"
+        );
+        let moves = [
+            ClockMove::Turn {
+                pins: "UR",
+                hours: 4,
+            },
+            ClockMove::Turn {
+                pins: "ALL",
+                hours: -2,
+            },
+            ClockMove::Flip,
+        ];
+        assert_eq!(Scramble::Clock(moves.to_vec()).to_string(), "UR4+ ALL2- y2");
+    }
+
+    #[test]
+    fn pyraminx_tips_come_last() {
+        let moves = pyraminx::gen_scramble(&mut StdRng::seed_from_u64(1));
+        let layers = moves.iter().take_while(|v| !v.tip).count();
+        assert!(moves[layers..].iter().all(|v| v.tip));
+        assert!(moves[..layers].windows(2).all(|v| v[0].vertex != v[1].vertex));
+    }
+}
@@ -0,0 +1,416 @@
+//! The Pyraminx: a model of its stickers, an optimal solver and random
+//! state scrambles. The edges and the centers under the tips can be
+//! arranged 6!/2 2^5 3^4 = 933,120 ways, all of them searched ahead of time
+//! for their distance from solved. Tips turn on their own and are fixed
+//! last.
+
+use super::sticker::{
+    even_perm_rank, even_perm_unrank, ori_rank, ori_unrank, turn, twist_rank, twist_unrank, Color,
+    Coordinate, DistanceTable, Orbit, Point, Stickers,
+};
+use rand::Rng;
+use std::f64::consts::PI;
+use std::fmt::{self, Formatter};
+use std::sync::OnceLock;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Vertex {
+    U,
+    L,
+    R,
+    B,
+}
+
+pub const VERTICES: [Vertex; 4] = [Vertex::U, Vertex::L, Vertex::R, Vertex::B];
+
+/// A turn of the layer under a vertex, or of just its tip (written in lower
+/// case).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PyraminxMove {
+    pub vertex: Vertex,
+    pub tip: bool,
+    pub prime: bool,
+}
+
+impl std::fmt::Display for PyraminxMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let c = match self.vertex {
+            Vertex::U => 'U',
+            Vertex::L => 'L',
+            Vertex::R => 'R',
+            Vertex::B => 'B',
+        };
+        let c = match self.tip {
+            true => c.to_ascii_lowercase(),
+            false => c,
+        };
+        let primestr = match self.prime {
+            true => "\'",
+            false => "",
+        };
+        write!(f, "{}{}", c, primestr)?;
+        Ok(())
+    }
+}
+
+/// Layer turns closer to solved than this are drawn again, as in WCA
+/// scrambles.
+pub const MIN_SCRAMBLE_LENGTH: usize = 6;
+
+/// Stickers on each face, in rows from its apex: 1, then 3, then 5.
+pub const FACE_STICKERS: usize = 9;
+
+/// The faces in sticker order, each with its color and the vertices at its
+/// apex, left and right as drawn in the net: F points up with L and R
+/// beside it and D under it, all three pointing down.
+pub const FACES: [(Color, [Vertex; 3]); 4] = [
+    (Color::Green, [Vertex::U, Vertex::L, Vertex::R]),
+    (Color::Red, [Vertex::L, Vertex::B, Vertex::U]),
+    (Color::Blue, [Vertex::R, Vertex::U, Vertex::B]),
+    (Color::Yellow, [Vertex::B, Vertex::L, Vertex::R]),
+];
+
+/// Corners of the tetrahedron, around its center.
+fn vertex_point(vertex: Vertex) -> Point {
+    match vertex {
+        Vertex::U => [1.0, 1.0, 1.0],
+        Vertex::L => [-1.0, -1.0, 1.0],
+        Vertex::R => [1.0, -1.0, -1.0],
+        Vertex::B => [-1.0, 1.0, -1.0],
+    }
+}
+
+fn vertex_index(vertex: Vertex) -> usize {
+    VERTICES.iter().position(|v| *v == vertex).unwrap_or(0)
+}
+
+/// Where a face's triangle grid point `(i, j)` is: `i` rows down from the
+/// apex and `j` across, in thirds of an edge.
+fn grid_point(corners: [Point; 3], i: f64, j: f64) -> Point {
+    let [apex, left, right] = corners;
+    [0, 1, 2].map(|k| apex[k] + i / 3.0 * (left[k] - apex[k]) + j / 3.0 * (right[k] - left[k]))
+}
+
+/// Row and place in the row of a face's sticker: rows hold 1, 3 and 5,
+/// alternately pointing like the face and the other way.
+pub fn sticker_place(i: usize) -> (usize, usize) {
+    let row = (i as f64).sqrt() as usize;
+    (row, i - row * row)
+}
+
+/// A face's stickers around a vertex: the tip, then the center under it.
+fn vertex_stickers(role: usize) -> (usize, usize) {
+    [(0, 2), (4, 5), (8, 7)][role]
+}
+
+/// A face's sticker on the edge between the vertices in two roles.
+fn edge_sticker(a: usize, b: usize) -> usize {
+    match (a.min(b), a.max(b)) {
+        (0, 1) => 1,
+        (0, 2) => 3,
+        _ => 6,
+    }
+}
+
+struct Model {
+    solved: Stickers,
+    /// Layer turns then tip turns, each vertex clockwise then
+    /// counterclockwise, in `VERTICES` order.
+    moves: Vec<Vec<usize>>,
+    edges: Orbit,
+    centers: Orbit,
+    tips: Orbit,
+}
+
+fn model() -> &'static Model {
+    static MODEL: OnceLock<Model> = OnceLock::new();
+    MODEL.get_or_init(|| {
+        let mut points = vec![];
+        let mut colors = vec![];
+        for (color, vertices) in FACES {
+            let corners = vertices.map(vertex_point);
+            for i in 0..FACE_STICKERS {
+                let (row, place) = sticker_place(i);
+                let (r, k) = (row as f64, (place / 2) as f64);
+                let triangle = match place % 2 {
+                    0 => [(r, k), (r + 1.0, k), (r + 1.0, k + 1.0)],
+                    _ => [(r, k), (r, k + 1.0), (r + 1.0, k + 1.0)],
+                };
+                let [a, b, c] = triangle.map(|(i, j)| grid_point(corners, i, j));
+                points.push([0, 1, 2].map(|k| (a[k] + b[k] + c[k]) / 3.0));
+                colors.push(color);
+            }
+        }
+        // Layers reach two thirds of the way to the opposite face, tips one
+        // third, measured along the axis of a vertex √3 from the center
+        let layer = 3f64.sqrt() / 9.0;
+        let tip = 5.0 * 3f64.sqrt() / 9.0;
+        let mut moves = vec![];
+        for min_dot in [layer, tip] {
+            for vertex in VERTICES {
+                for angle in [2.0 * PI / 3.0, -2.0 * PI / 3.0] {
+                    moves.push(turn(&points, vertex_point(vertex), min_dot, angle));
+                }
+            }
+        }
+        let mut tips = vec![vec![]; 4];
+        let mut centers = vec![vec![]; 4];
+        let mut edges: Vec<(usize, usize, Vec<usize>)> = vec![];
+        for (f, (_, vertices)) in FACES.iter().enumerate() {
+            for (role, vertex) in vertices.iter().enumerate() {
+                let (t, c) = vertex_stickers(role);
+                tips[vertex_index(*vertex)].push(f * FACE_STICKERS + t);
+                centers[vertex_index(*vertex)].push(f * FACE_STICKERS + c);
+                for (other_role, other) in vertices.iter().enumerate().skip(role + 1) {
+                    let key = {
+                        let (a, b) = (vertex_index(*vertex), vertex_index(*other));
+                        (a.min(b), a.max(b))
+                    };
+                    let sticker = f * FACE_STICKERS + edge_sticker(role, other_role);
+                    match edges.iter_mut().find(|v| (v.0, v.1) == key) {
+                        Some(v) => v.2.push(sticker),
+                        None => edges.push((key.0, key.1, vec![sticker])),
+                    }
+                }
+            }
+        }
+        Model {
+            solved: Stickers { colors },
+            moves,
+            edges: Orbit::new(edges.into_iter().map(|v| v.2).collect(), &points),
+            centers: Orbit::new(centers, &points),
+            tips: Orbit::new(tips, &points),
+        }
+    })
+}
+
+fn move_index(mv: PyraminxMove) -> usize {
+    (mv.tip as usize) * 8 + vertex_index(mv.vertex) * 2 + mv.prime as usize
+}
+
+fn layer_move(m: usize) -> PyraminxMove {
+    PyraminxMove {
+        vertex: VERTICES[m / 2],
+        tip: false,
+        prime: m % 2 == 1,
+    }
+}
+
+/// A Pyraminx, as the color of each of its 36 stickers: faces in `FACES`
+/// order, each in rows from its apex.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PyraminxState {
+    pub stickers: Stickers,
+}
+
+impl PyraminxState {
+    pub fn solved() -> Self {
+        Self {
+            stickers: model().solved.clone(),
+        }
+    }
+
+    pub fn apply(&mut self, mv: PyraminxMove) {
+        self.stickers.apply(&model().moves[move_index(mv)]);
+    }
+
+    pub fn apply_all(&mut self, moves: &[PyraminxMove]) {
+        for mv in moves {
+            self.apply(*mv);
+        }
+    }
+
+    /// Stickers of face `f` of `FACES`, in rows from its apex.
+    pub fn face(&self, f: usize) -> &[Color] {
+        &self.stickers.colors[f * FACE_STICKERS..(f + 1) * FACE_STICKERS]
+    }
+
+    /// Values of the coordinates of the distance table: the edges'
+    /// arrangement and flips, and the centers' twists.
+    fn coordinates(&self) -> Option<[usize; 3]> {
+        let model = model();
+        let (ep, eo) = model.edges.read(&self.stickers, &model.solved)?;
+        let (_, co) = model.centers.read(&self.stickers, &model.solved)?;
+        Some([even_perm_rank(&ep), twist_rank(&eo, 2), ori_rank(&co, 3)])
+    }
+}
+
+fn distances() -> &'static DistanceTable {
+    static TABLE: OnceLock<DistanceTable> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let model = model();
+        let layer_moves = &model.moves[..8];
+        let edges = |perm: &[u8], ori: &[u8]| {
+            let mut state = model.solved.clone();
+            model.edges.write(&mut state, &model.solved, perm, ori);
+            state
+        };
+        let (home, unturned) = model.edges.home();
+        let get = |state: &Stickers| PyraminxState {
+            stickers: state.clone(),
+        };
+        let coordinates = vec![
+            Coordinate::new(
+                360,
+                layer_moves,
+                |v| {
+                    let mut perm = [0; 6];
+                    even_perm_unrank(v, &mut perm);
+                    edges(&perm, &unturned)
+                },
+                |state| get(state).coordinates().map_or(0, |v| v[0]),
+            ),
+            Coordinate::new(
+                32,
+                layer_moves,
+                |v| {
+                    let mut ori = [0; 6];
+                    twist_unrank(v, 2, &mut ori);
+                    edges(&home, &ori)
+                },
+                |state| get(state).coordinates().map_or(0, |v| v[1]),
+            ),
+            Coordinate::new(
+                81,
+                layer_moves,
+                |v| {
+                    let mut state = model.solved.clone();
+                    let mut ori = [0; 4];
+                    ori_unrank(v, 3, &mut ori);
+                    let (home, _) = model.centers.home();
+                    model.centers.write(&mut state, &model.solved, &home, &ori);
+                    state
+                },
+                |state| get(state).coordinates().map_or(0, |v| v[2]),
+            ),
+        ];
+        DistanceTable::new(coordinates, &[0, 0, 0])
+    })
+}
+
+/// Turns of the tips that are out of place.
+fn tip_fixes(state: &PyraminxState) -> Vec<PyraminxMove> {
+    let model = model();
+    let twisted = |state: &PyraminxState, vertex: Vertex| {
+        model
+            .tips
+            .read(&state.stickers, &model.solved)
+            .is_some_and(|(_, ori)| ori[vertex_index(vertex)] != 0)
+    };
+    VERTICES
+        .into_iter()
+        .filter(|v| twisted(state, *v))
+        .filter_map(|vertex| {
+            [false, true]
+                .map(|prime| PyraminxMove {
+                    vertex,
+                    tip: true,
+                    prime,
+                })
+                .into_iter()
+                .find(|mv| {
+                    let mut turned = state.clone();
+                    turned.apply(*mv);
+                    !twisted(&turned, vertex)
+                })
+        })
+        .collect()
+}
+
+/// An optimal solution: the fewest layer turns, then a turn of each tip
+/// they leave out of place. Returns `None` if the stickers aren't a
+/// Pyraminx that can be solved.
+pub fn solve(state: &PyraminxState) -> Option<Vec<PyraminxMove>> {
+    let layers = distances().solve(&state.coordinates()?)?;
+    let mut moves: Vec<PyraminxMove> = layers.into_iter().map(layer_move).collect();
+    let mut solved = state.clone();
+    solved.apply_all(&moves);
+    moves.extend(tip_fixes(&solved));
+    Some(moves)
+}
+
+/// A random state scramble: layer turns taking the puzzle to a uniformly
+/// random state at least `MIN_SCRAMBLE_LENGTH` of them from solved, found
+/// by undoing an optimal solution to it, followed by a random turn (or
+/// none) of each tip.
+pub fn gen_scramble<R: Rng + ?Sized>(rng: &mut R) -> Vec<PyraminxMove> {
+    let table = distances();
+    let solution = table
+        .solve(&table.random(MIN_SCRAMBLE_LENGTH, rng))
+        .expect("random states are solvable");
+    let mut moves: Vec<PyraminxMove> = solution
+        .into_iter()
+        .rev()
+        .map(|m| PyraminxMove {
+            prime: m % 2 == 0,
+            ..layer_move(m)
+        })
+        .collect();
+    for vertex in VERTICES {
+        match rng.gen_range(0..3) {
+            0 => (),
+            v => moves.push(PyraminxMove {
+                vertex,
+                tip: true,
+                prime: v == 2,
+            }),
+        }
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn turns_come_back_around() {
+        let mut state = PyraminxState::solved();
+        for vertex in VERTICES {
+            for tip in [false, true] {
+                let mv = PyraminxMove {
+                    vertex,
+                    tip,
+                    prime: false,
+                };
+                state.apply(mv);
+                assert_ne!(state, PyraminxState::solved());
+                state.apply(PyraminxMove { prime: true, ..mv });
+                assert_eq!(state, PyraminxState::solved());
+                state.apply_all(&[mv, mv, mv]);
+                assert_eq!(state, PyraminxState::solved());
+            }
+        }
+        // U turns the F face's top two rows to L
+        state.apply(PyraminxMove {
+            vertex: Vertex::U,
+            tip: false,
+            prime: false,
+        });
+        assert_eq!(state.face(1)[8], Color::Green);
+        assert_eq!(state.face(1)[4], Color::Red);
+    }
+
+    #[test]
+    fn searches_every_state() {
+        assert_eq!(distances().reachable(), 933_120);
+    }
+
+    #[test]
+    fn scrambles_are_solved_optimally() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            let scramble = gen_scramble(&mut rng);
+            let mut state = PyraminxState::solved();
+            state.apply_all(&scramble);
+            let solution = solve(&state).unwrap();
+            assert!(solution.len() <= scramble.len());
+            let layers = scramble.iter().filter(|v| !v.tip).count();
+            assert!(layers >= MIN_SCRAMBLE_LENGTH);
+            assert_eq!(solution.iter().filter(|v| !v.tip).count(), layers);
+            state.apply_all(&solution);
+            assert_eq!(state, PyraminxState::solved());
+        }
+    }
+}
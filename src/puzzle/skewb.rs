@@ -0,0 +1,370 @@
+//! The Skewb, in the WCA notation where R, U, L and B turn the half of the
+//! puzzle around the DRB, ULB, DLF and DLB corners: a model of its stickers,
+//! an optimal solver and random state scrambles. None of those turns moves
+//! UFR, and the rest can be arranged 3,149,280 ways, all of them searched
+//! ahead of time for their distance from solved.
+
+use super::sticker::{
+    even_perm_rank, even_perm_unrank, ori_rank, turn, Color, Coordinate, DistanceTable, Orbit,
+    Point, Stickers,
+};
+use rand::Rng;
+use std::f64::consts::PI;
+use std::fmt::{self, Formatter};
+use std::sync::OnceLock;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum SkewbAxis {
+    R,
+    U,
+    L,
+    B,
+}
+
+pub const SKEWB_AXES: [SkewbAxis; 4] = [SkewbAxis::R, SkewbAxis::U, SkewbAxis::L, SkewbAxis::B];
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SkewbMove {
+    pub axis: SkewbAxis,
+    pub prime: bool,
+}
+
+impl std::fmt::Display for SkewbMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let c = match self.axis {
+            SkewbAxis::R => 'R',
+            SkewbAxis::U => 'U',
+            SkewbAxis::L => 'L',
+            SkewbAxis::B => 'B',
+        };
+        let primestr = match self.prime {
+            true => "\'",
+            false => "",
+        };
+        write!(f, "{}{}", c, primestr)?;
+        Ok(())
+    }
+}
+
+/// Random states closer to solved than this are drawn again, as in WCA
+/// scrambles.
+pub const MIN_SCRAMBLE_LENGTH: usize = 7;
+
+/// Stickers on each face: the center, then the corners clockwise from the
+/// top left as drawn in the net.
+pub const FACE_STICKERS: usize = 5;
+
+/// The faces in sticker order, laid out as in the cube net: each with its
+/// color, the direction it faces and the directions right and down on it.
+/// `x` points right, `y` up and `z` to the front.
+pub const FACES: [(Color, [Point; 3]); 6] = [
+    (
+        Color::White,
+        [[0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+    ),
+    (
+        Color::Red,
+        [[1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, -1.0, 0.0]],
+    ),
+    (
+        Color::Green,
+        [[0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, -1.0, 0.0]],
+    ),
+    (
+        Color::Yellow,
+        [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]],
+    ),
+    (
+        Color::Orange,
+        [[-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, -1.0, 0.0]],
+    ),
+    (
+        Color::Blue,
+        [[0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]],
+    ),
+];
+
+/// Steps right and down from a face's center to each of its corners.
+const CORNERS: [(f64, f64); 4] = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+
+/// The corner each axis turns around.
+fn axis_point(axis: SkewbAxis) -> Point {
+    match axis {
+        SkewbAxis::R => [1.0, -1.0, -1.0],
+        SkewbAxis::U => [-1.0, 1.0, -1.0],
+        SkewbAxis::L => [-1.0, -1.0, 1.0],
+        SkewbAxis::B => [-1.0, -1.0, -1.0],
+    }
+}
+
+struct Model {
+    solved: Stickers,
+    /// Each axis clockwise then counterclockwise, in `SKEWB_AXES` order.
+    moves: Vec<Vec<usize>>,
+    centers: Orbit,
+    /// The half of the corners the turns go round, with UFR, which never
+    /// moves, last.
+    fixed_corners: Orbit,
+    /// The other half, three of which every turn cycles.
+    free_corners: Orbit,
+}
+
+fn model() -> &'static Model {
+    static MODEL: OnceLock<Model> = OnceLock::new();
+    MODEL.get_or_init(|| {
+        let mut points = vec![];
+        let mut colors = vec![];
+        // Stickers of each corner, by the corner's position
+        let mut corners: Vec<(Point, Vec<usize>)> = vec![];
+        for (f, (color, [normal, right, down])) in FACES.iter().enumerate() {
+            points.push(*normal);
+            colors.push(*color);
+            for (k, (x, y)) in CORNERS.iter().enumerate() {
+                let corner = [0, 1, 2].map(|i| normal[i] + x * right[i] + y * down[i]);
+                points
+                    .push([0, 1, 2].map(|i| normal[i] + 2.0 / 3.0 * (x * right[i] + y * down[i])));
+                colors.push(*color);
+                let sticker = f * FACE_STICKERS + 1 + k;
+                match corners.iter_mut().find(|v| v.0 == corner) {
+                    Some(v) => v.1.push(sticker),
+                    None => corners.push((corner, vec![sticker])),
+                }
+            }
+        }
+        let mut moves = vec![];
+        for axis in SKEWB_AXES {
+            for angle in [2.0 * PI / 3.0, -2.0 * PI / 3.0] {
+                moves.push(turn(&points, axis_point(axis), 0.0, angle));
+            }
+        }
+        // Corners split in two by the sign of the product of their
+        // coordinates, and turns never move a corner to the other half
+        let (mut fixed, free): (Vec<_>, Vec<_>) = corners
+            .into_iter()
+            .partition(|(p, _)| p[0] * p[1] * p[2] > 0.0);
+        fixed.sort_by_key(|(p, _)| *p == [1.0, 1.0, 1.0]);
+        let centers = (0..FACES.len()).map(|f| vec![f * FACE_STICKERS]).collect();
+        Model {
+            solved: Stickers { colors },
+            moves,
+            centers: Orbit::new(centers, &points),
+            fixed_corners: Orbit::new(fixed.into_iter().map(|v| v.1).collect(), &points),
+            free_corners: Orbit::new(free.into_iter().map(|v| v.1).collect(), &points),
+        }
+    })
+}
+
+fn move_index(mv: SkewbMove) -> usize {
+    SKEWB_AXES.iter().position(|v| *v == mv.axis).unwrap_or(0) * 2 + mv.prime as usize
+}
+
+fn index_move(m: usize) -> SkewbMove {
+    SkewbMove {
+        axis: SKEWB_AXES[m / 2],
+        prime: m % 2 == 1,
+    }
+}
+
+/// A Skewb, as the color of each of its 30 stickers: faces in `FACES`
+/// order, each its center then its corners.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SkewbState {
+    pub stickers: Stickers,
+}
+
+impl SkewbState {
+    pub fn solved() -> Self {
+        Self {
+            stickers: model().solved.clone(),
+        }
+    }
+
+    pub fn apply(&mut self, mv: SkewbMove) {
+        self.stickers.apply(&model().moves[move_index(mv)]);
+    }
+
+    pub fn apply_all(&mut self, moves: &[SkewbMove]) {
+        for mv in moves {
+            self.apply(*mv);
+        }
+    }
+
+    /// Stickers of face `f` of `FACES`: the center, then the corners.
+    pub fn face(&self, f: usize) -> &[Color] {
+        &self.stickers.colors[f * FACE_STICKERS..(f + 1) * FACE_STICKERS]
+    }
+
+    /// Values of the coordinates of the distance table: how the centers
+    /// are arranged, and the number `corners` gives how the corners are.
+    fn coordinates(&self) -> Option<[usize; 2]> {
+        let model = model();
+        let (cp, _) = model.centers.read(&self.stickers, &model.solved)?;
+        let corners = corners().values[raw_corners(&self.stickers)?];
+        if corners == u32::MAX {
+            return None;
+        }
+        Some([even_perm_rank(&cp), corners as usize])
+    }
+}
+
+/// How the corners other than UFR are arranged and turned, counting every
+/// twist, or `None` if UFR isn't solved.
+fn raw_corners(stickers: &Stickers) -> Option<usize> {
+    let model = model();
+    let (fp, fo) = model.fixed_corners.read(stickers, &model.solved)?;
+    let (free_p, free_o) = model.free_corners.read(stickers, &model.solved)?;
+    if fp[3] != 3 || fo[3] != 0 {
+        return None;
+    }
+    let perm = even_perm_rank(&fp[..3]) * 12 + even_perm_rank(&free_p);
+    Some((perm * 27 + ori_rank(&fo[..3], 3)) * 81 + ori_rank(&free_o, 3))
+}
+
+/// The corners' arrangements a Skewb can reach, numbered in the order a
+/// search from solved finds them. The twists of each half add up to what
+/// the other half's arrangement allows, so only 8748 of the 78732 raw
+/// values are ever reached.
+struct Corners {
+    /// The number of each raw value, or `u32::MAX` if it can't be reached.
+    values: Vec<u32>,
+    /// A state with the corners of each number and the centers solved.
+    states: Vec<Stickers>,
+}
+
+fn corners() -> &'static Corners {
+    static CORNERS: OnceLock<Corners> = OnceLock::new();
+    CORNERS.get_or_init(|| {
+        let model = model();
+        let mut values = vec![u32::MAX; 3 * 12 * 27 * 81];
+        values[raw_corners(&model.solved).expect("solved")] = 0;
+        let mut states = vec![model.solved.clone()];
+        let mut i = 0;
+        while i < states.len() {
+            for perm in &model.moves {
+                let mut turned = states[i].clone();
+                turned.apply(perm);
+                let raw = raw_corners(&turned).expect("turns leave UFR alone");
+                if values[raw] == u32::MAX {
+                    values[raw] = states.len() as u32;
+                    states.push(turned);
+                }
+            }
+            i += 1;
+        }
+        Corners { values, states }
+    })
+}
+
+fn distances() -> &'static DistanceTable {
+    static TABLE: OnceLock<DistanceTable> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let model = model();
+        let get = |i: usize| {
+            move |state: &Stickers| {
+                SkewbState {
+                    stickers: state.clone(),
+                }
+                .coordinates()
+                .map_or(0, |v| v[i])
+            }
+        };
+        let corners = corners();
+        let coordinates = vec![
+            Coordinate::new(
+                360,
+                &model.moves,
+                |v| {
+                    let mut state = model.solved.clone();
+                    let mut perm = [0; 6];
+                    even_perm_unrank(v, &mut perm);
+                    model
+                        .centers
+                        .write(&mut state, &model.solved, &perm, &[0; 6]);
+                    state
+                },
+                get(0),
+            ),
+            Coordinate::new(
+                corners.states.len(),
+                &model.moves,
+                |v| corners.states[v].clone(),
+                get(1),
+            ),
+        ];
+        DistanceTable::new(coordinates, &[0, 0])
+    })
+}
+
+/// An optimal solution, or `None` if the stickers aren't a Skewb with UFR
+/// solved that can be solved.
+pub fn solve(state: &SkewbState) -> Option<Vec<SkewbMove>> {
+    let moves = distances().solve(&state.coordinates()?)?;
+    Some(moves.into_iter().map(index_move).collect())
+}
+
+/// A random state scramble: a uniformly random Skewb at least
+/// `MIN_SCRAMBLE_LENGTH` moves from solved, reached by undoing an optimal
+/// solution to it.
+pub fn gen_scramble<R: Rng + ?Sized>(rng: &mut R) -> Vec<SkewbMove> {
+    let table = distances();
+    let solution = table
+        .solve(&table.random(MIN_SCRAMBLE_LENGTH, rng))
+        .expect("random states are solvable");
+    solution
+        .into_iter()
+        .rev()
+        .map(|m| SkewbMove {
+            prime: m % 2 == 0,
+            ..index_move(m)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn turns_come_back_around() {
+        let mut state = SkewbState::solved();
+        for axis in SKEWB_AXES {
+            let mv = SkewbMove { axis, prime: false };
+            state.apply(mv);
+            assert_ne!(state, SkewbState::solved());
+            state.apply(SkewbMove { prime: true, ..mv });
+            assert_eq!(state, SkewbState::solved());
+            state.apply_all(&[mv, mv, mv]);
+            assert_eq!(state, SkewbState::solved());
+        }
+        // R turns the D center to the R face, clockwise seen from DRB
+        state.apply(SkewbMove {
+            axis: SkewbAxis::R,
+            prime: false,
+        });
+        assert_eq!(state.face(1)[0], Color::Yellow);
+        assert_eq!(state.face(0)[0], Color::White);
+    }
+
+    #[test]
+    fn searches_every_state() {
+        assert_eq!(corners().states.len(), 8748);
+        assert_eq!(distances().reachable(), 3_149_280);
+    }
+
+    #[test]
+    fn scrambles_are_solved_optimally() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..20 {
+            let scramble = gen_scramble(&mut rng);
+            assert!(scramble.len() >= MIN_SCRAMBLE_LENGTH);
+            assert!(scramble.windows(2).all(|v| v[0].axis != v[1].axis));
+            let mut state = SkewbState::solved();
+            state.apply_all(&scramble);
+            let solution = solve(&state).unwrap();
+            assert_eq!(solution.len(), scramble.len());
+            state.apply_all(&solution);
+            assert_eq!(state, SkewbState::solved());
+        }
+    }
+}
@@ -0,0 +1,844 @@
+//! The Square-1 in the WCA notation: `(x,y)` turns the top layer `x`
+//! and the bottom layer `y` twelfths of a turn clockwise, and `/` turns the
+//! right half of the puzzle by 180°. Random states are solved in two
+//! phases: back into a square shape that `/` keeps, with the pieces an even
+//! arrangement away from solved, then to solved with twists that lead from
+//! one such shape to another, each followed by `/`.
+
+use crate::cube::cubie::{parity, perm_rank, perm_unrank};
+use rand::{seq::SliceRandom, Rng};
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+use std::fmt::{self, Formatter};
+use std::sync::OnceLock;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Square1Move {
+    Twist(i32, i32),
+    Slice,
+}
+
+impl std::fmt::Display for Square1Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Square1Move::Twist(top, bottom) => write!(f, "({},{})", top, bottom)?,
+            Square1Move::Slice => write!(f, "/")?,
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    /// Anything other than a twist like `(1,0)` or a `/`.
+    UnknownMove,
+    /// A twist that isn't two numbers from -6 to 6.
+    BadTwist,
+    /// A `/` with a corner lying across the slice.
+    Blocked,
+}
+
+/// Where and why a Square-1 scramble could not be read.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    /// Column of the first character of the offending token, counting from 1.
+    pub column: usize,
+    pub token: String,
+    pub kind: ParseErrorKind,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        let reason = match self.kind {
+            ParseErrorKind::UnknownMove => "unknown move",
+            ParseErrorKind::BadTwist => "bad twist",
+            ParseErrorKind::Blocked => "a corner blocks",
+        };
+        write!(f, "{} `{}` at column {}", reason, self.token, self.column)?;
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The pieces of both layers as twelve 30° slots each, seen from above and
+/// counted clockwise from the back end of the slice, so that `/` swaps
+/// slots 0 to 5 of the layers. A corner fills two neighbouring slots and an
+/// edge one, and each slot holds its piece's number: corners 0 to 7, then
+/// edges 8 to 15, the first four of each on top.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Square1 {
+    pub top: [u8; 12],
+    pub bottom: [u8; 12],
+    /// Whether the right half of the middle layer is turned over, leaving
+    /// the middle out of square.
+    pub flipped: bool,
+}
+
+impl Square1 {
+    pub fn solved() -> Self {
+        Self {
+            top: [0, 0, 8, 1, 1, 9, 2, 2, 10, 3, 3, 11],
+            bottom: [4, 4, 12, 5, 5, 13, 6, 6, 14, 7, 7, 15],
+            flipped: false,
+        }
+    }
+
+    /// Whether `/` can be done: no corner lies across the slice in either
+    /// layer.
+    pub fn can_slice(&self) -> bool {
+        [&self.top, &self.bottom]
+            .iter()
+            .all(|v| v[11] != v[0] && v[5] != v[6])
+    }
+
+    /// Turns the top layer `top` and the bottom layer `bottom` twelfths of a
+    /// turn clockwise, each seen from its own side.
+    pub fn twist(&mut self, top: i32, bottom: i32) {
+        self.top.rotate_right(top.rem_euclid(12) as usize);
+        self.bottom.rotate_left(bottom.rem_euclid(12) as usize);
+    }
+
+    /// Turns the right half over, which mirrors it front to back. Only
+    /// meaningful if `can_slice`.
+    pub fn slice(&mut self) {
+        for i in 0..6 {
+            std::mem::swap(&mut self.top[i], &mut self.bottom[5 - i]);
+        }
+        self.flipped = !self.flipped;
+    }
+
+    /// Does `mv`, unless it is a `/` that a corner blocks. Returns whether
+    /// it was done.
+    pub fn apply(&mut self, mv: Square1Move) -> bool {
+        match mv {
+            Square1Move::Twist(top, bottom) => self.twist(top, bottom),
+            Square1Move::Slice if self.can_slice() => self.slice(),
+            Square1Move::Slice => return false,
+        }
+        true
+    }
+
+    pub fn is_solved(&self) -> bool {
+        *self == Self::solved()
+    }
+
+    /// The layers' shapes, as the slots where a corner starts going
+    /// clockwise, and whether the pieces read in slot order, top then
+    /// bottom, are an odd permutation. Moves change the parity by an amount
+    /// that only depends on the shapes, so the two make up one coordinate.
+    fn shape(&self) -> u32 {
+        let starts = |layer: &[u8; 12]| {
+            (0..12)
+                .filter(|i| layer[*i] < 8 && layer[(i + 1) % 12] == layer[*i])
+                .fold(0, |acc, i| acc | 1 << i)
+        };
+        let mut pieces = [0; 16];
+        let mut n = 0;
+        for layer in [&self.top, &self.bottom] {
+            for i in 0..12 {
+                if n < 16 && (layer[i] >= 8 || layer[(i + 1) % 12] == layer[i]) {
+                    pieces[n] = layer[i];
+                    n += 1;
+                }
+            }
+        }
+        let odd = parity(&pieces[..n]);
+        starts(&self.top) | starts(&self.bottom) << 12 | (odd as u32) << 24
+    }
+
+    /// Corners then edges in `square` of the square shapes, in slot order.
+    fn pieces(&self, square: usize) -> ([u8; 8], [u8; 8]) {
+        let mut corners = [0; 8];
+        let mut edges = [0; 8];
+        for (k, (layer, offset)) in [(&self.top, square), (&self.bottom, 1 - square)]
+            .into_iter()
+            .enumerate()
+        {
+            for i in 0..4 {
+                corners[4 * k + i] = layer[3 * i + offset];
+                edges[4 * k + i] = layer[3 * i + (offset + 2) % 3] - 8;
+            }
+        }
+        (corners, edges)
+    }
+
+    fn from_pieces(corners: &[u8; 8], edges: &[u8; 8], flipped: bool, square: usize) -> Self {
+        let mut state = Self {
+            top: [0; 12],
+            bottom: [0; 12],
+            flipped,
+        };
+        for (k, (layer, offset)) in [(&mut state.top, square), (&mut state.bottom, 1 - square)]
+            .into_iter()
+            .enumerate()
+        {
+            for i in 0..4 {
+                layer[3 * i + offset] = corners[4 * k + i];
+                layer[3 * i + offset + 1] = corners[4 * k + i];
+                layer[3 * i + (offset + 2) % 3] = edges[4 * k + i] + 8;
+            }
+        }
+        state
+    }
+
+    /// Which of the square shapes the layers are in, if any.
+    fn square(&self) -> Option<usize> {
+        let home: [u8; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
+        (0..N_SQUARES).find(|v| {
+            let shape = Self::from_pieces(&home, &home, false, *v).shape();
+            (shape ^ self.shape()) & 0xff_ffff == 0
+        })
+    }
+}
+
+/// Reads a scramble like `(1,0)/ (-1,2)/`, checking every `/` can be done
+/// on a solved puzzle turned by the moves before it.
+pub fn parse_moves(s: &str) -> Result<Vec<Square1Move>, ParseError> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut puzzle = Square1::solved();
+    let mut moves = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let mv = match chars[i] {
+            '/' => {
+                i += 1;
+                Ok(Square1Move::Slice)
+            }
+            '(' => {
+                while i < chars.len() && chars[i] != ')' {
+                    i += 1;
+                }
+                i = (i + 1).min(chars.len());
+                let token: String = chars[start..i].iter().collect();
+                parse_twist(&token)
+            }
+            _ => {
+                while i < chars.len() && !chars[i].is_whitespace() && chars[i] != '/' {
+                    i += 1;
+                }
+                Err(ParseErrorKind::UnknownMove)
+            }
+        };
+        let error = |kind| ParseError {
+            column: start + 1,
+            token: chars[start..i].iter().collect(),
+            kind,
+        };
+        let mv = mv.map_err(error)?;
+        if !puzzle.apply(mv) {
+            return Err(error(ParseErrorKind::Blocked));
+        }
+        moves.push(mv);
+    }
+    Ok(moves)
+}
+
+/// Reads a twist such as `(1, -2)`.
+fn parse_twist(token: &str) -> Result<Square1Move, ParseErrorKind> {
+    let inner = token
+        .strip_prefix('(')
+        .and_then(|v| v.strip_suffix(')'))
+        .ok_or(ParseErrorKind::BadTwist)?;
+    let amounts = inner
+        .split(',')
+        .map(|v| v.trim().parse::<i32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ParseErrorKind::BadTwist)?;
+    match amounts[..] {
+        [top, bottom] if (-6..=6).contains(&top) && (-6..=6).contains(&bottom) => {
+            Ok(Square1Move::Twist(top, bottom))
+        }
+        _ => Err(ParseErrorKind::BadTwist),
+    }
+}
+
+const N_PERM: usize = 40320;
+
+/// Arrangements of eight pieces with the lowest of each layer's four first,
+/// which stand for all of them turned by quarter turns. Those turns don't
+/// change how many `/` are needed, so the distance tables only keep these.
+const N_REPS: usize = 2520;
+
+/// Ways to turn the two layers' four pieces by quarter turns.
+const N_TURNS: usize = 16;
+
+/// Ways to pick the four positions the first four pieces of an orbit are
+/// in.
+const N_SPLIT: usize = 70;
+
+/// The square shapes that `/` keeps, by which layer starts with a corner
+/// at the back end of the slice: the top, as in `square()`, or the bottom.
+const N_SQUARES: usize = 2;
+
+/// Solved with the bottom layer a twelfth of a turn off, the first of the
+/// square shapes.
+fn square() -> Square1 {
+    let mut state = Square1::solved();
+    state.twist(0, -1);
+    state
+}
+
+/// A twist that `/` can follow from a square shape to get to one again,
+/// and where the two take the corners and edges: the piece in place
+/// `corners[i]` ends up in place `i`.
+struct SquareMove {
+    top: i32,
+    bottom: i32,
+    to: usize,
+    corners: [u8; 8],
+    edges: [u8; 8],
+}
+
+fn permute(pieces: &[u8; 8], map: &[u8; 8]) -> [u8; 8] {
+    map.map(|i| pieces[i as usize])
+}
+
+/// `pieces` with the top four turned `turn / 4` places and the bottom four
+/// `turn % 4`.
+fn rotate(pieces: &[u8; 8], turn: usize) -> [u8; 8] {
+    let mut rotated = *pieces;
+    rotated[..4].rotate_left(turn / 4);
+    rotated[4..].rotate_left(turn % 4);
+    rotated
+}
+
+fn inverse_turn(turn: usize) -> usize {
+    (4 - turn / 4) % 4 * 4 + (4 - turn % 4) % 4
+}
+
+/// How far to rotate `pieces` to get the lowest of each layer first.
+fn lowest_first(pieces: &[u8; 8]) -> usize {
+    let lowest = |layer: &[u8]| (0..4).min_by_key(|i| layer[*i]).unwrap_or(0);
+    lowest(&pieces[..4]) * 4 + lowest(&pieces[4..])
+}
+
+/// Which positions hold the first four pieces, as bits.
+fn first_four(pieces: &[u8; 8]) -> usize {
+    (0..8).fold(0, |acc, i| acc | ((pieces[i] < 4) as usize) << i)
+}
+
+/// A state in the square shapes: the corners and the edges each as the
+/// arrangement with the lowest pieces first and how far it is turned from
+/// that, and the middle.
+#[derive(Clone, Copy)]
+struct SquareState {
+    square: usize,
+    corners: (usize, usize),
+    edges: (usize, usize),
+    flip: usize,
+}
+
+/// Moves and distances for one orbit, by square shape.
+struct Orbit {
+    /// Where each move takes each arrangement with the lowest pieces
+    /// first, and how far to turn the result to get them first again.
+    moves: Vec<Vec<(u16, u8)>>,
+    /// Number of `/` needed to get the orbit, the positions of the other
+    /// orbit's first four and the middle a twist away from solved, turned
+    /// so the orbit's lowest pieces come first.
+    distances: Vec<u8>,
+}
+
+struct Tables {
+    /// Distance from the square shape of every shape reachable from solved,
+    /// in `shape_moves`.
+    shapes: HashMap<u32, u8>,
+    moves: [Vec<SquareMove>; N_SQUARES],
+    /// For each square shape and turn, the move that does to an arrangement
+    /// what each move does to it turned back.
+    turned_moves: Vec<Vec<u8>>,
+    /// The arrangements with the lowest pieces first, and the index of
+    /// each among them by rank.
+    reps: Vec<[u8; 8]>,
+    rep_index: Vec<u16>,
+    /// Rank among the `N_SPLIT` of each choice of four positions, as bits,
+    /// what each turn makes of them, and which ones each arrangement with
+    /// the lowest pieces first has its first four in.
+    splits: Vec<u8>,
+    split_turns: Vec<[u8; N_TURNS]>,
+    rep_splits: Vec<u8>,
+    corners: Orbit,
+    edges: Orbit,
+    /// Square shape, corners and edges of every state a twist away from
+    /// solved.
+    solved: HashSet<(usize, [u8; 8], [u8; 8])>,
+}
+
+impl Tables {
+    fn rep(&self, pieces: &[u8; 8]) -> (usize, usize) {
+        let turn = lowest_first(pieces);
+        let rep = self.rep_index[perm_rank(&rotate(pieces, turn))] as usize;
+        (rep, turn)
+    }
+
+    fn square_state(
+        &self,
+        square: usize,
+        corners: &[u8; 8],
+        edges: &[u8; 8],
+        flip: usize,
+    ) -> SquareState {
+        SquareState {
+            square,
+            corners: self.rep(corners),
+            edges: self.rep(edges),
+            flip,
+        }
+    }
+
+    /// Distance table index of `(rep, turn)` with the other orbit's
+    /// `(other_rep, other_turn)`.
+    fn index(
+        &self,
+        square: usize,
+        (rep, turn): (usize, usize),
+        (other_rep, other_turn): (usize, usize),
+        flip: usize,
+    ) -> usize {
+        let relative =
+            (turn / 4 + 4 - other_turn / 4) % 4 * 4 + (turn % 4 + 4 - other_turn % 4) % 4;
+        let split = self.split_turns[self.rep_splits[other_rep] as usize][relative];
+        ((square * N_REPS + rep) * N_SPLIT + split as usize) * 2 + flip
+    }
+
+    /// Lower bound on the number of `/` that `state` needs.
+    fn bound(&self, state: &SquareState) -> u8 {
+        let (square, flip) = (state.square, state.flip);
+        let corners = self.index(square, state.corners, state.edges, flip);
+        let edges = self.index(square, state.edges, state.corners, flip);
+        self.corners.distances[corners].max(self.edges.distances[edges])
+    }
+
+    fn apply(&self, state: &SquareState, m: usize) -> SquareState {
+        let square = state.square;
+        let orbit = |orbit: &Orbit, (rep, turn): (usize, usize)| {
+            let m = self.turned_moves[square * N_TURNS + turn][m] as usize;
+            let (rep, turn) = orbit.moves[square * N_REPS + rep][m];
+            (rep as usize, turn as usize)
+        };
+        SquareState {
+            square: self.moves[square][m].to,
+            corners: orbit(&self.corners, state.corners),
+            edges: orbit(&self.edges, state.edges),
+            flip: state.flip ^ 1,
+        }
+    }
+
+    fn is_solved(&self, state: &SquareState) -> bool {
+        let pieces = |(rep, turn): (usize, usize)| rotate(&self.reps[rep], inverse_turn(turn));
+        state.flip == 0
+            && self
+                .solved
+                .contains(&(state.square, pieces(state.corners), pieces(state.edges)))
+    }
+
+    /// Move tables and a breadth-first search counting `/` from the states
+    /// a twist away from solved, for the corners or the edges.
+    fn orbit(&self, corners: bool) -> Orbit {
+        let map = |mv: &SquareMove, corners: bool| match corners {
+            true => mv.corners,
+            false => mv.edges,
+        };
+        let mut moves = vec![];
+        // Where each move takes the positions of the other orbit's first four
+        let mut split_moves = vec![];
+        for square_moves in &self.moves {
+            for perm in &self.reps {
+                moves.push(
+                    square_moves
+                        .iter()
+                        .map(|mv| {
+                            let (rep, turn) = self.rep(&permute(perm, &map(mv, corners)));
+                            (rep as u16, turn as u8)
+                        })
+                        .collect(),
+                );
+            }
+            let mut rows = vec![vec![]; N_SPLIT];
+            for mask in (0..256usize).filter(|v| v.count_ones() == 4) {
+                let pieces =
+                    [0, 1, 2, 3, 4, 5, 6, 7].map(|i| if mask >> i & 1 == 1 { 0 } else { 4 });
+                rows[self.splits[mask] as usize] = square_moves
+                    .iter()
+                    .map(|mv| self.splits[first_four(&permute(&pieces, &map(mv, !corners)))])
+                    .collect();
+            }
+            split_moves.extend(rows);
+        }
+        let mut distances = vec![u8::MAX; N_SQUARES * N_REPS * N_SPLIT * 2];
+        let mut queue = VecDeque::new();
+        for (square, corner_pieces, edge_pieces) in &self.solved {
+            let (pieces, others) = match corners {
+                true => (corner_pieces, edge_pieces),
+                false => (edge_pieces, corner_pieces),
+            };
+            let index = self.index(*square, self.rep(pieces), self.rep(others), 0);
+            if distances[index] == u8::MAX {
+                distances[index] = 0;
+                queue.push_back(index);
+            }
+        }
+        while let Some(index) = queue.pop_front() {
+            let (flip, split) = (index % 2, index / 2 % N_SPLIT);
+            let (square, rep) = (index / 2 / N_SPLIT / N_REPS, index / 2 / N_SPLIT % N_REPS);
+            let rep_moves: &Vec<(u16, u8)> = &moves[square * N_REPS + rep];
+            for (m, (rep, turn)) in rep_moves.iter().enumerate() {
+                let split = split_moves[square * N_SPLIT + split][m] as usize;
+                let split = self.split_turns[split][*turn as usize] as usize;
+                let to = self.moves[square][m].to;
+                let next = ((to * N_REPS + *rep as usize) * N_SPLIT + split) * 2 + (flip ^ 1);
+                if distances[next] == u8::MAX {
+                    distances[next] = distances[index] + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+        Orbit { moves, distances }
+    }
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let mut shapes = HashMap::from([(square().shape(), 0)]);
+        let mut queue = VecDeque::from([square()]);
+        while let Some(state) = queue.pop_front() {
+            let d = shapes[&state.shape()];
+            for mv in shape_moves() {
+                let mut next = state;
+                if next.apply(mv) {
+                    if let Entry::Vacant(v) = shapes.entry(next.shape()) {
+                        v.insert(d + 1);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        let home: [u8; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
+        let moves = [0, 1].map(|square| {
+            let state = Square1::from_pieces(&home, &home, false, square);
+            (0..144)
+                .filter_map(|v| {
+                    let mut next = state;
+                    next.twist(v / 12, v % 12);
+                    next.apply(Square1Move::Slice).then_some(())?;
+                    let to = next.square()?;
+                    let (corners, edges) = next.pieces(to);
+                    Some(SquareMove {
+                        top: v / 12,
+                        bottom: v % 12,
+                        to,
+                        corners,
+                        edges,
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+        // Turning first is the same as twisting a quarter turn further
+        let mut turned_moves = vec![];
+        for square_moves in &moves {
+            for turn in 0..N_TURNS {
+                let turned = rotate(&home, inverse_turn(turn));
+                turned_moves.push(
+                    square_moves
+                        .iter()
+                        .map(|mv| {
+                            let (corners, edges) =
+                                (permute(&turned, &mv.corners), permute(&turned, &mv.edges));
+                            let same = |v: &SquareMove| {
+                                v.corners == corners && v.edges == edges && v.to == mv.to
+                            };
+                            square_moves
+                                .iter()
+                                .position(same)
+                                .expect("twists go round by quarter turns")
+                                as u8
+                        })
+                        .collect(),
+                );
+            }
+        }
+        let mut reps = vec![];
+        let mut rep_index = vec![u16::MAX; N_PERM];
+        for (r, index) in rep_index.iter_mut().enumerate() {
+            let mut perm = [0; 8];
+            perm_unrank(r, &mut perm);
+            if lowest_first(&perm) == 0 {
+                *index = reps.len() as u16;
+                reps.push(perm);
+            }
+        }
+        let splits: Vec<u8> = (0..256usize)
+            .map(|mask| (0..mask).filter(|v| v.count_ones() == 4).count() as u8)
+            .collect();
+        let mut split_turns = vec![[0; N_TURNS]; N_SPLIT];
+        for mask in (0..256usize).filter(|v| v.count_ones() == 4) {
+            let pieces = home.map(|i| if mask >> i & 1 == 1 { 0 } else { 4 });
+            split_turns[splits[mask] as usize] =
+                std::array::from_fn(|turn| splits[first_four(&rotate(&pieces, turn))]);
+        }
+        let rep_splits = reps.iter().map(|v| splits[first_four(v)]).collect();
+        let mut solved = HashSet::new();
+        for v in 0..144 {
+            let mut state = Square1::solved();
+            state.twist(v / 12, v % 12);
+            if let Some(square) = state.square() {
+                let (corners, edges) = state.pieces(square);
+                solved.insert((square, corners, edges));
+            }
+        }
+        let no_orbit = || Orbit {
+            moves: vec![],
+            distances: vec![],
+        };
+        let mut tables = Tables {
+            shapes,
+            moves,
+            turned_moves,
+            reps,
+            rep_index,
+            splits,
+            split_turns,
+            rep_splits,
+            corners: no_orbit(),
+            edges: no_orbit(),
+            solved,
+        };
+        tables.corners = tables.orbit(true);
+        tables.edges = tables.orbit(false);
+        tables
+    })
+}
+
+/// Twists of one layer and `/`, for getting between shapes.
+fn shape_moves() -> impl Iterator<Item = Square1Move> {
+    (1..12)
+        .map(|v| Square1Move::Twist(v, 0))
+        .chain((1..12).map(|v| Square1Move::Twist(0, v)))
+        .chain([Square1Move::Slice])
+}
+
+/// Depth-first search for `depth` more twists each followed by `/` that
+/// take `state` a twist away from solved, given by their index in
+/// `Tables::moves`.
+fn search(tables: &Tables, state: &SquareState, depth: usize, moves: &mut Vec<usize>) -> bool {
+    if tables.bound(state) as usize > depth {
+        return false;
+    }
+    if depth == 0 {
+        return tables.is_solved(state);
+    }
+    for m in 0..tables.moves[state.square].len() {
+        moves.push(m);
+        if search(tables, &tables.apply(state, m), depth - 1, moves) {
+            return true;
+        }
+        moves.pop();
+    }
+    false
+}
+
+/// Adds `mv` to `moves`, merging twists and cancelling slices.
+fn push(moves: &mut Vec<Square1Move>, mv: Square1Move) {
+    let merged = match (moves.last(), mv) {
+        (Some(Square1Move::Slice), Square1Move::Slice) => None,
+        (Some(Square1Move::Twist(a, b)), Square1Move::Twist(c, d)) => {
+            Some(Square1Move::Twist(a + c, b + d))
+        }
+        _ => {
+            moves.push(normalize(mv));
+            return;
+        }
+    };
+    moves.pop();
+    match merged.map(normalize) {
+        Some(Square1Move::Twist(0, 0)) | None => {}
+        Some(v) => moves.push(v),
+    }
+}
+
+/// Twists written from -5 to 6 twelfths of a turn.
+fn normalize(mv: Square1Move) -> Square1Move {
+    let amount = |v: i32| (v + 5).rem_euclid(12) - 5;
+    match mv {
+        Square1Move::Twist(top, bottom) => Square1Move::Twist(amount(top), amount(bottom)),
+        Square1Move::Slice => Square1Move::Slice,
+    }
+}
+
+/// A solution, or `None` if the pieces aren't those of a Square-1. Each
+/// phase takes the fewest `/` it can, but the whole isn't the shortest.
+pub fn solve(state: &Square1) -> Option<Vec<Square1Move>> {
+    let tables = tables();
+    let mut state = *state;
+    let mut moves = vec![];
+    let mut d = *tables.shapes.get(&state.shape())?;
+    while d > 0 {
+        (d, state) = shape_moves().find_map(|mv| {
+            let mut next = state;
+            let closer = next.apply(mv) && tables.shapes.get(&next.shape()) == Some(&(d - 1));
+            closer.then(|| {
+                push(&mut moves, mv);
+                (d - 1, next)
+            })
+        })?;
+    }
+    let mut square = state.square()?;
+    let (corners, edges) = state.pieces(square);
+    let start = tables.square_state(square, &corners, &edges, state.flipped as usize);
+    let mut square_moves = vec![];
+    (0..=u8::MAX as usize).find(|depth| search(tables, &start, *depth, &mut square_moves))?;
+    for m in square_moves {
+        let mv = &tables.moves[square][m];
+        for mv in [Square1Move::Twist(mv.top, mv.bottom), Square1Move::Slice] {
+            state.apply(mv);
+            push(&mut moves, mv);
+        }
+        square = mv.to;
+    }
+    let last = (0..144)
+        .map(|v| Square1Move::Twist(v / 12, v % 12))
+        .find(|mv| {
+            let mut next = state;
+            next.apply(*mv);
+            next.is_solved()
+        })?;
+    push(&mut moves, last);
+    Some(moves)
+}
+
+/// A random state scramble: a uniformly random shape and arrangement of
+/// the pieces, reached by undoing a solution to it.
+pub fn gen_scramble<R: Rng + ?Sized>(rng: &mut R) -> Vec<Square1Move> {
+    let mut shapes: Vec<u32> = tables().shapes.keys().copied().collect();
+    shapes.sort();
+    let shape = *shapes.choose(rng).expect("the square shape is reachable");
+    let state = random_state(shape, rng);
+    let solution = solve(&state).expect("random states are solvable");
+    let mut moves = vec![];
+    for mv in solution.into_iter().rev() {
+        push(
+            &mut moves,
+            match mv {
+                Square1Move::Twist(top, bottom) => Square1Move::Twist(-top, -bottom),
+                Square1Move::Slice => Square1Move::Slice,
+            },
+        );
+    }
+    moves
+}
+
+/// The pieces shuffled into the layers' shapes from `Square1::shape`,
+/// swapping two edges if that gets the parity wrong.
+fn random_state<R: Rng + ?Sized>(shape: u32, rng: &mut R) -> Square1 {
+    let mut corners: Vec<u8> = (0..8).collect();
+    let mut edges: Vec<u8> = (8..16).collect();
+    corners.shuffle(rng);
+    edges.shuffle(rng);
+    let mut state = Square1 {
+        top: [0; 12],
+        bottom: [0; 12],
+        flipped: rng.gen(),
+    };
+    let mut edge_slots = vec![];
+    for (k, layer) in [&mut state.top, &mut state.bottom].into_iter().enumerate() {
+        let starts = |i: usize| shape >> (12 * k + i % 12) & 1 == 1;
+        for i in 0..12 {
+            if starts(i) {
+                let corner = corners.pop().unwrap_or(0);
+                layer[i] = corner;
+                layer[(i + 1) % 12] = corner;
+            } else if !starts(i + 11) {
+                layer[i] = edges.pop().unwrap_or(8);
+                edge_slots.push((k, i));
+            }
+        }
+    }
+    if state.shape() != shape {
+        let [(ka, a), (kb, b)] = [edge_slots[0], edge_slots[1]];
+        let layers = [state.top, state.bottom];
+        let (va, vb) = (layers[ka][a], layers[kb][b]);
+        for (k, i, v) in [(ka, a, vb), (kb, b, va)] {
+            match k {
+                0 => state.top[i] = v,
+                _ => state.bottom[i] = v,
+            }
+        }
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn corners_across_the_slice_block_it() {
+        let mut puzzle = Square1::solved();
+        assert!(puzzle.can_slice());
+        puzzle.twist(1, 0);
+        assert!(puzzle.can_slice());
+        puzzle.twist(1, 0);
+        assert!(!puzzle.can_slice());
+        puzzle.twist(-2, 3);
+        assert!(puzzle.can_slice());
+    }
+
+    #[test]
+    fn reads_scrambles_and_rejects_blocked_slices() {
+        let moves = parse_moves("(1,0)/ (-1, 2) /(0,-3)").unwrap();
+        assert_eq!(
+            moves,
+            [
+                Square1Move::Twist(1, 0),
+                Square1Move::Slice,
+                Square1Move::Twist(-1, 2),
+                Square1Move::Slice,
+                Square1Move::Twist(0, -3),
+            ]
+        );
+        let e = parse_moves("/ (2,0)/").unwrap_err();
+        assert_eq!((e.column, e.kind), (8, ParseErrorKind::Blocked));
+        let e = parse_moves("(1,0)/ (7,0)").unwrap_err();
+        assert_eq!(
+            (e.token.as_str(), e.kind),
+            ("(7,0)", ParseErrorKind::BadTwist)
+        );
+        let e = parse_moves("(1,0) R").unwrap_err();
+        assert_eq!((e.column, e.kind), (7, ParseErrorKind::UnknownMove));
+    }
+
+    #[test]
+    fn slices_flip_the_middle() {
+        let mut puzzle = Square1::solved();
+        for mv in parse_moves("/ (6,0)/ (6,0)/").unwrap() {
+            assert!(puzzle.apply(mv));
+        }
+        assert!(puzzle.flipped);
+        assert!(!puzzle.is_solved());
+    }
+
+    #[test]
+    fn random_states_are_solved() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..3 {
+            let scramble = gen_scramble(&mut rng);
+            assert!(scramble.windows(2).all(|v| v[0] != v[1]));
+            let mut puzzle = Square1::solved();
+            for mv in &scramble {
+                assert!(puzzle.apply(*mv));
+            }
+            let solution = solve(&puzzle).unwrap();
+            for mv in solution {
+                assert!(puzzle.apply(mv));
+            }
+            assert!(puzzle.is_solved());
+        }
+    }
+}
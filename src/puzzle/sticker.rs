@@ -0,0 +1,378 @@
+//! Puzzles modelled by their stickers, shared by the puzzles other than
+//! cubes. A state is the color of every sticker and a move the permutation
+//! of stickers it makes, worked out by turning the puzzle's shape in 3D.
+//! Pieces are read off the stickers orbit by orbit, giving the coordinates
+//! that optimal solvers search over.
+
+use super::super::cube::cubie::{parity, perm_rank, perm_unrank};
+use rand::Rng;
+use std::collections::VecDeque;
+
+/// Sticker colors, the last six only used on the Megaminx.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Color {
+    White,
+    Yellow,
+    Green,
+    Blue,
+    Red,
+    Orange,
+    Purple,
+    Gray,
+    LightYellow,
+    Pink,
+    LightGreen,
+    LightBlue,
+}
+
+pub type Point = [f64; 3];
+
+/// Colors of a puzzle's stickers, numbered the way its model lays them out.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Stickers {
+    pub colors: Vec<Color>,
+}
+
+impl Stickers {
+    /// Moves every sticker to where `perm` sends it: sticker `perm[i]` ends
+    /// up in place `i`.
+    pub fn apply(&mut self, perm: &[usize]) {
+        let old = self.colors.clone();
+        for (i, from) in perm.iter().enumerate() {
+            self.colors[i] = old[*from];
+        }
+    }
+}
+
+pub fn dot(a: Point, b: Point) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross(a: Point, b: Point) -> Point {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub fn unit(a: Point) -> Point {
+    let length = dot(a, a).sqrt();
+    a.map(|v| v / length)
+}
+
+/// `p` turned by `angle` radians around `axis`, counterclockwise seen from
+/// the end of the axis.
+fn rotate(p: Point, axis: Point, angle: f64) -> Point {
+    let k = unit(axis);
+    let (sin, cos) = angle.sin_cos();
+    let kxp = cross(k, p);
+    let kp = dot(k, p) * (1.0 - cos);
+    [0, 1, 2].map(|i| p[i] * cos + kxp[i] * sin + k[i] * kp)
+}
+
+/// The permutation made by turning every sticker further than `min_dot`
+/// along `axis` clockwise by `angle`, seen from the end of the axis.
+/// Stickers are given by their centers, and land on the nearest one.
+pub fn turn(centers: &[Point], axis: Point, min_dot: f64, angle: f64) -> Vec<usize> {
+    let k = unit(axis);
+    let mut perm: Vec<usize> = (0..centers.len()).collect();
+    for (from, p) in centers.iter().enumerate() {
+        if dot(*p, k) <= min_dot {
+            continue;
+        }
+        let turned = rotate(*p, axis, -angle);
+        let distance = |q: &Point| (0..3).map(|i| (q[i] - turned[i]).powi(2)).sum::<f64>();
+        let to = (0..centers.len())
+            .min_by(|a, b| distance(&centers[*a]).total_cmp(&distance(&centers[*b])))
+            .unwrap_or(from);
+        perm[to] = from;
+    }
+    perm
+}
+
+/// Pieces that move among the same positions, each position listed as its
+/// stickers. Stickers of a piece with three go round it clockwise, so a
+/// piece's orientation is how far its stickers are turned from solved.
+pub struct Orbit {
+    pub positions: Vec<Vec<usize>>,
+}
+
+impl Orbit {
+    /// Puts the stickers of each position in clockwise order seen from
+    /// outside, going round the middle of their centers.
+    pub fn new(positions: Vec<Vec<usize>>, centers: &[Point]) -> Self {
+        let positions = positions
+            .into_iter()
+            .map(|mut stickers| {
+                if stickers.len() == 3 {
+                    let [a, b, c] = [0, 1, 2].map(|i| centers[stickers[i]]);
+                    let normal = cross(
+                        [0, 1, 2].map(|i| b[i] - a[i]),
+                        [0, 1, 2].map(|i| c[i] - a[i]),
+                    );
+                    // Counterclockwise when the normal points outwards
+                    if dot(normal, a) > 0.0 {
+                        stickers.swap(1, 2);
+                    }
+                }
+                stickers
+            })
+            .collect();
+        Self { positions }
+    }
+
+    /// The piece in each position and its orientation, or `None` if some
+    /// position's stickers aren't those of a piece of `solved`.
+    pub fn read(&self, state: &Stickers, solved: &Stickers) -> Option<(Vec<u8>, Vec<u8>)> {
+        let mut perm = vec![];
+        let mut ori = vec![];
+        for stickers in &self.positions {
+            let colors: Vec<_> = stickers.iter().map(|v| state.colors[*v]).collect();
+            let (piece, turned) = self.positions.iter().enumerate().find_map(|(j, home)| {
+                let n = home.len();
+                (0..n)
+                    .find(|o| (0..n).all(|k| colors[k] == solved.colors[home[(k + o) % n]]))
+                    .map(|o| (j, o))
+            })?;
+            perm.push(piece as u8);
+            ori.push(turned as u8);
+        }
+        Some((perm, ori))
+    }
+
+    /// Puts piece `perm[i]` into position `i` with orientation `ori[i]`.
+    pub fn write(&self, state: &mut Stickers, solved: &Stickers, perm: &[u8], ori: &[u8]) {
+        for (i, stickers) in self.positions.iter().enumerate() {
+            let home = &self.positions[perm[i] as usize];
+            let n = home.len();
+            for (k, v) in stickers.iter().enumerate() {
+                state.colors[*v] = solved.colors[home[(k + ori[i] as usize) % n]];
+            }
+        }
+    }
+
+    /// The solved arrangement of the orbit: every piece home, unturned.
+    pub fn home(&self) -> (Vec<u8>, Vec<u8>) {
+        let n = self.positions.len();
+        ((0..n as u8).collect(), vec![0; n])
+    }
+}
+
+/// Rank of an even permutation among the even ones.
+pub fn even_perm_rank(perm: &[u8]) -> usize {
+    // Swapping the last two pieces changes only the lowest bit of the rank
+    perm_rank(perm) / 2
+}
+
+pub fn even_perm_unrank(rank: usize, perm: &mut [u8]) {
+    perm_unrank(rank * 2, perm);
+    if parity(perm) {
+        let n = perm.len();
+        perm.swap(n - 2, n - 1);
+    }
+}
+
+/// Orientations of pieces written as a number in base `base`.
+pub fn ori_rank(ori: &[u8], base: usize) -> usize {
+    ori.iter().fold(0, |acc, v| acc * base + *v as usize)
+}
+
+pub fn ori_unrank(mut rank: usize, base: usize, ori: &mut [u8]) {
+    for v in ori.iter_mut().rev() {
+        *v = (rank % base) as u8;
+        rank /= base;
+    }
+}
+
+/// Orientations of pieces that always add up to a multiple of `base`,
+/// written leaving out the last one.
+pub fn twist_rank(ori: &[u8], base: usize) -> usize {
+    ori_rank(&ori[..ori.len() - 1], base)
+}
+
+pub fn twist_unrank(rank: usize, base: usize, ori: &mut [u8]) {
+    let n = ori.len();
+    ori_unrank(rank, base, &mut ori[..n - 1]);
+    let sum: usize = ori[..n - 1].iter().map(|v| *v as usize).sum();
+    ori[n - 1] = ((base - sum % base) % base) as u8;
+}
+
+/// Something about a state that moves change on their own, like where an
+/// orbit's pieces are or how they are turned: for each of its values, the
+/// value each move takes it to.
+pub struct Coordinate {
+    pub size: usize,
+    moves: Vec<Vec<u32>>,
+}
+
+impl Coordinate {
+    /// Works out the move table by setting up a state with each value,
+    /// turning it and reading the value back.
+    pub fn new(
+        size: usize,
+        moves: &[Vec<usize>],
+        set: impl Fn(usize) -> Stickers,
+        get: impl Fn(&Stickers) -> usize,
+    ) -> Self {
+        let moves = (0..size)
+            .map(|v| {
+                let state = set(v);
+                moves
+                    .iter()
+                    .map(|perm| {
+                        let mut turned = state.clone();
+                        turned.apply(perm);
+                        get(&turned) as u32
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { size, moves }
+    }
+}
+
+/// The exact number of moves from every state to solved, over the states
+/// made up of the values of some coordinates, found by a breadth-first
+/// search from solved. Values that can't be reached together stay unknown.
+pub struct DistanceTable {
+    coordinates: Vec<Coordinate>,
+    n_moves: usize,
+    distances: Vec<u8>,
+}
+
+impl DistanceTable {
+    /// Searches from `solved`, the values of the coordinates when solved.
+    pub fn new(coordinates: Vec<Coordinate>, solved: &[usize]) -> Self {
+        let size = coordinates.iter().map(|v| v.size).product();
+        let n_moves = coordinates.first().map_or(0, |v| v.moves[0].len());
+        let mut table = Self {
+            coordinates,
+            n_moves,
+            distances: vec![u8::MAX; size],
+        };
+        let start = table.index(solved);
+        table.distances[start] = 0;
+        let mut queue = VecDeque::from([start as u32]);
+        while let Some(index) = queue.pop_front() {
+            let d = table.distances[index as usize];
+            for m in 0..n_moves {
+                let next = table.apply(index as usize, m);
+                if table.distances[next] == u8::MAX {
+                    table.distances[next] = d + 1;
+                    queue.push_back(next as u32);
+                }
+            }
+        }
+        table
+    }
+
+    pub fn index(&self, values: &[usize]) -> usize {
+        self.coordinates
+            .iter()
+            .zip(values)
+            .fold(0, |acc, (c, v)| acc * c.size + v)
+    }
+
+    fn apply(&self, mut index: usize, m: usize) -> usize {
+        // The last coordinate changes fastest, as in `index`
+        let (mut next, mut step) = (0, 1);
+        for c in self.coordinates.iter().rev() {
+            next += c.moves[index % c.size][m] as usize * step;
+            step *= c.size;
+            index /= c.size;
+        }
+        next
+    }
+
+    /// Number of states the search reached.
+    #[cfg(test)]
+    pub fn reachable(&self) -> usize {
+        self.distances.iter().filter(|v| **v != u8::MAX).count()
+    }
+
+    /// Moves of an optimal solution, by index in the move list the
+    /// coordinates were set up with, or `None` if the state can't be
+    /// solved.
+    pub fn solve(&self, values: &[usize]) -> Option<Vec<usize>> {
+        let mut index = self.index(values);
+        if self.distances[index] == u8::MAX {
+            return None;
+        }
+        let mut moves = vec![];
+        while self.distances[index] > 0 {
+            let d = self.distances[index];
+            let m = (0..self.n_moves).find(|m| self.distances[self.apply(index, *m)] < d)?;
+            index = self.apply(index, m);
+            moves.push(m);
+        }
+        Some(moves)
+    }
+
+    /// Coordinate values of a uniformly random solvable state at least
+    /// `min_length` moves from solved.
+    pub fn random<R: Rng + ?Sized>(&self, min_length: usize, rng: &mut R) -> Vec<usize> {
+        let index = loop {
+            let index = rng.gen_range(0..self.distances.len());
+            let d = self.distances[index];
+            if d != u8::MAX && d as usize >= min_length {
+                break index;
+            }
+        };
+        let mut rest = index;
+        let mut values = vec![0; self.coordinates.len()];
+        for (c, v) in self.coordinates.iter().zip(values.iter_mut()).rev() {
+            *v = rest % c.size;
+            rest /= c.size;
+        }
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quarter_turns_come_back_around() {
+        // The six face centers of a cube
+        let centers = [
+            [1.0, 0.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, -1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, -1.0],
+        ];
+        let y = turn(&centers, [0.0, 1.0, 0.0], -2.0, std::f64::consts::FRAC_PI_2);
+        // Clockwise from above, the front goes to the left
+        assert_eq!(y[1], 4);
+        let mut state = Stickers {
+            colors: vec![
+                Color::Red,
+                Color::Orange,
+                Color::White,
+                Color::Yellow,
+                Color::Green,
+                Color::Blue,
+            ],
+        };
+        let solved = state.clone();
+        for _ in 0..4 {
+            state.apply(&y);
+        }
+        assert_eq!(state, solved);
+    }
+
+    #[test]
+    fn even_permutations_rank_in_half() {
+        let mut ranks = vec![];
+        for rank in 0..12 {
+            let mut perm = [0; 4];
+            even_perm_unrank(rank, &mut perm);
+            assert!(!parity(&perm));
+            ranks.push(even_perm_rank(&perm));
+        }
+        assert_eq!(ranks, (0..12).collect::<Vec<usize>>());
+    }
+}
@@ -1,6 +1,29 @@
 use super::app::*;
+use super::cube::{
+    bld::{Lettering, Memo, Trace},
+    cross::CrossSolution,
+    cube2::Inspection,
+    filter::MAX_MIN_LENGTH,
+    ll::{self, Case},
+    moves_to_string,
+    nxn::{NxnMove, NxnState},
+    recon::RECON_METRIC,
+    sequence::{Metric, METRICS},
+    validate::{CORNER_NAMES, EDGE_NAMES},
+    CubeState, Face, FACELET_ORDER,
+};
+use super::puzzle::{
+    daily::DAILY_SCRAMBLES,
+    megaminx::{self, MegaminxState},
+    pyraminx::PyraminxState,
+    skewb::SkewbState,
+    square1::Square1,
+    sticker::Color as StickerColor,
+    Puzzle, PuzzleState,
+};
 use crossterm::event::{self, Event, KeyCode, KeyModifiers};
 use std::{
+    cmp::Ordering,
     env,
     error::Error,
     path::Path,
@@ -9,9 +32,9 @@ use std::{
 use tui::{
     backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     symbols,
-    text::Span,
+    text::{Span, Spans, Text},
     widgets::{
         Axis, Block, Borders, Cell, Chart, Dataset, GraphType, List, ListItem, Paragraph, Row,
         Table, Wrap,
@@ -19,9 +42,8 @@ use tui::{
     Frame, Terminal,
 };
 
-const HELP_TEXT: &'static str = include_str!("../text/help.txt");
-const WELCOME_TEXT: &'static str = include_str!("../text/welcome.txt");
-const CUBE_TEXT: &'static str = include_str!("../text/cube.txt");
+const HELP_TEXT: &str = include_str!("../text/help.txt");
+const WELCOME_TEXT: &str = include_str!("../text/welcome.txt");
 
 pub fn run<B: Backend>(terminal: &mut Terminal<B>) -> Result<(), Box<dyn Error>> {
     // Create app and load times
@@ -29,6 +51,10 @@ pub fn run<B: Backend>(terminal: &mut Terminal<B>) -> Result<(), Box<dyn Error>>
     let path = Path::new(&pathstr);
     let mut app = App::new(Duration::from_millis(1000), path)?;
     app.load_times()?;
+    app.load_lettering()?;
+    app.load_patterns()?;
+    app.load_keymap()?;
+    app.load_filter_stats()?;
 
     // Main loop and tick logic
     let mut last_tick = Instant::now();
@@ -43,10 +69,8 @@ pub fn run<B: Backend>(terminal: &mut Terminal<B>) -> Result<(), Box<dyn Error>>
             .tick_rate
             .checked_sub(last_tick.elapsed())
             .unwrap_or(Duration::from_secs(0));
-        if event::poll(timeout)? {
-            if handle_input(&mut app)? {
-                return Ok(());
-            }
+        if event::poll(timeout)? && handle_input(&mut app)? {
+            return Ok(());
         }
         if last_tick.elapsed() >= app.tick_rate {
             app.on_tick();
@@ -57,18 +81,34 @@ pub fn run<B: Backend>(terminal: &mut Terminal<B>) -> Result<(), Box<dyn Error>>
 
 fn handle_input(app: &mut App) -> Result<bool, Box<dyn Error>> {
     if let Event::Key(key) = event::read()? {
+        if app.typing() && matches!(key.modifiers, KeyModifiers::NONE | KeyModifiers::SHIFT) {
+            match key.code {
+                KeyCode::Char(c) => app.type_char(c),
+                KeyCode::Backspace => app.backspace(),
+                KeyCode::Enter => app.submit(),
+                KeyCode::Up => app.arrow(Dir::Up),
+                KeyCode::Down => app.arrow(Dir::Down),
+                KeyCode::Left => app.arrow(Dir::Left),
+                KeyCode::Right => app.arrow(Dir::Right),
+                KeyCode::Esc => app.esc(),
+                _ => (),
+            }
+            return Ok(false);
+        }
         match key.modifiers {
             KeyModifiers::NONE => match key.code {
                 KeyCode::Char('q') => {
                     app.write_times()?;
                     return Ok(true);
                 }
+                KeyCode::Char(' ') if app.puzzle == Puzzle::Fmc => app.start_fmc(),
+                // No timing the solved cube shown until the scramble is found
+                KeyCode::Char(' ') if app.filter.searching() && !app.timer.on => (),
                 KeyCode::Char(' ') => match app.timer.space_press() {
-                    Some(mut t) => {
-                        t.gen_stats(&app.times.times);
-                        app.times.insert(t);
+                    Some(t) => {
+                        app.record(t);
                         app.tick_rate = Duration::from_millis(1000);
-                        app.new_scramble();
+                        app.next_scramble();
                     }
                     None => app.tick_rate = Duration::from_millis(100),
                 },
@@ -79,6 +119,9 @@ fn handle_input(app: &mut App) -> Result<bool, Box<dyn Error>> {
                 KeyCode::Char('k') => app.mv(Dir::Up),
                 KeyCode::Char('l') => app.mv(Dir::Right),
                 KeyCode::Char('d') => app.del(),
+                KeyCode::Char('p') => app.next_puzzle()?,
+                KeyCode::Char('m') => app.next_subset()?,
+                KeyCode::Char('r') => app.toggle_daily(),
                 KeyCode::Char('?') => app.help(),
                 _ => (),
             },
@@ -118,11 +161,19 @@ fn render_default<B: Backend>(f: &mut Frame<B>, app: &mut App) {
         )
         .split(chunks[0]);
 
+    // Long scrambles for big cubes need more than the usual three lines
+    let scramble_width = chunks[1].width.saturating_sub(2).max(1) as usize;
+    let scramble_lines = app
+        .scramble_text()
+        .lines()
+        .map(|v| v.len() / scramble_width + 1)
+        .sum::<usize>()
+        + 1;
     let right_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
             [
-                Constraint::Length(5),
+                Constraint::Length(scramble_lines as u16 + 3),
                 Constraint::Length(3),
                 Constraint::Percentage(100),
             ]
@@ -172,11 +223,11 @@ fn render_help_and_tools<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chu
 
     let border_style = app.get_border_style_from_id(ActiveBlock::Tools);
     let selected_style = app.get_highlight_style_from_id(ActiveBlock::Tools);
-    let items = [
-        ListItem::new(Tool::Welcome.to_string()),
-        ListItem::new(Tool::Chart.to_string()),
-        ListItem::new(Tool::Cube.to_string()),
-    ];
+    let items: Vec<ListItem> = app
+        .tools
+        .iter()
+        .map(|v| ListItem::new(v.to_string()))
+        .collect();
     let list = List::new(items)
         .block(
             Block::default()
@@ -191,10 +242,18 @@ fn render_help_and_tools<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chu
 }
 
 fn render_timer<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
-    let text = format!("\n\n{}", app.timer.text());
     let borderstyle = app.get_border_style_from_id(ActiveBlock::Timer);
     let mut paragraphstyle = Style::default();
-    paragraphstyle = match app.timer.on {
+    // FMC counts the hour down instead
+    let text = match app.puzzle {
+        Puzzle::Fmc => format!("\n\n{}", app.fmc.countdown()),
+        _ => format!("\n\n{}", app.timer.text()),
+    };
+    let running = match app.puzzle {
+        Puzzle::Fmc => app.fmc.remaining().is_some(),
+        _ => app.timer.on,
+    };
+    paragraphstyle = match running {
         false => match app.timer.lasttime {
             Some(_) => paragraphstyle.fg(Color::LightBlue),
             None => paragraphstyle.fg(Color::White),
@@ -217,24 +276,38 @@ fn render_timer<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect)
 fn render_times<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
     let selected_style = app.get_highlight_style_from_id(ActiveBlock::Times);
     let normal_style = Style::default().fg(Color::White);
-    let header_cells = ["i", "time", "ao5", "ao12"].iter().map(|h| Cell::from(*h));
+    // FMC results are move counts, averaged over three
+    let fmc = app.puzzle == Puzzle::Fmc;
+    let headers = match fmc {
+        true => ["i", "moves", "mo3", "ao5"],
+        false => ["i", "time", "ao5", "ao12"],
+    };
+    let header_cells = headers.iter().map(|h| Cell::from(*h));
     let header = Row::new(header_cells)
         .style(normal_style)
         .height(1)
         .bottom_margin(1);
     let numrows = app.times.times.len();
     let rows = app.times.times.iter().rev().enumerate().map(|(i, t)| {
-        let ao5 = match t.ao5 {
-            Some(v) => format!("{:.2}", v),
+        let (first, second) = match fmc {
+            true => (t.mo3, t.ao5),
+            false => (t.ao5, t.ao12),
+        };
+        let ao5 = match first {
+            Some(v) => result_text(v, 2),
             None => "-".to_string(),
         };
-        let ao12 = match t.ao12 {
-            Some(v) => format!("{:.2}", v),
+        let ao12 = match second {
+            Some(v) => result_text(v, 2),
             None => "-".to_string(),
         };
+        let single = match fmc {
+            true => result_text(t.time, 0),
+            false => result_text(t.time, 2),
+        };
         let cells = vec![
             (numrows-i).to_string(),
-            format!("{:.2}", t.time),
+            single,
             format!("{}", ao5),
             format!("{}", ao12),
         ];
@@ -261,10 +334,50 @@ fn render_times<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect)
 
 fn render_scramble<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
     let border_style = app.get_border_style_from_id(ActiveBlock::Scramble);
-    let paragraph = Paragraph::new(format!("\n{}", app.scramble.clone()))
+    // While stepping through the scramble, the last move done stands out
+    // and the ones still to do are dimmed
+    let (text, step) = match (app.active_tool, app.playback, app.scramble.steps()) {
+        (Tool::Cube, Some(done), Some(steps)) => {
+            let spans: Vec<Span> = steps
+                .iter()
+                .enumerate()
+                .flat_map(|(i, v)| {
+                    let style = match (i + 1).cmp(&done) {
+                        Ordering::Less => Style::default(),
+                        Ordering::Equal => Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                        Ordering::Greater => Style::default().fg(Color::DarkGray),
+                    };
+                    [Span::styled(v.clone(), style), Span::raw(" ")]
+                })
+                .collect();
+            let text = Text::from(vec![Spans::from(""), Spans::from(spans)]);
+            (text, format!(", move {}/{}", done, steps.len()))
+        }
+        _ if app.filter.searching() => (
+            Text::from("\nLooking for a scramble that passes the filter..."),
+            String::new(),
+        ),
+        _ => (
+            Text::from(format!("\n{}", app.scramble_text())),
+            String::new(),
+        ),
+    };
+    let paragraph = Paragraph::new(text)
         .block(
             Block::default()
-                .title("Scramble")
+                .title(match app.daily {
+                    Some((day, i)) => format!(
+                        "Scramble ({}, daily {} {}/{}{})",
+                        app.puzzle,
+                        day,
+                        i + 1,
+                        DAILY_SCRAMBLES,
+                        step
+                    ),
+                    None => format!("Scramble ({}{})", app.puzzle, step),
+                })
                 .borders(Borders::ALL)
                 .border_style(border_style),
         )
@@ -290,14 +403,31 @@ fn render_bests<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect)
         )
         .split(layout_chunk);
 
-    render_stat(f, app, "PB Single", app.times.pbsingle, chunks[0]);
-    render_stat(f, app, "PB ao5", app.times.pbao5, chunks[1]);
-    render_stat(f, app, "PB ao12", app.times.pbao12, chunks[2]);
+    match app.puzzle {
+        Puzzle::Fmc => {
+            render_stat(f, app, "PB Single", app.times.pbsingle, chunks[0]);
+            render_stat(f, app, "PB mo3", app.times.pbmo3, chunks[1]);
+            render_stat(f, app, "PB ao5", app.times.pbao5, chunks[2]);
+        }
+        _ => {
+            render_stat(f, app, "PB Single", app.times.pbsingle, chunks[0]);
+            render_stat(f, app, "PB ao5", app.times.pbao5, chunks[1]);
+            render_stat(f, app, "PB ao12", app.times.pbao12, chunks[2]);
+        }
+    }
     render_stat(f, app, "ao100", app.times.ao100, chunks[3]);
     render_stat(f, app, "ao1k", app.times.ao1k, chunks[4]);
     render_stat(f, app, "avg", app.times.rollingavg, chunks[5]);
 }
 
+/// A time, move count or average with `decimals` decimals, or DNF.
+fn result_text(v: f64, decimals: usize) -> String {
+    match v == DNF {
+        true => "DNF".to_string(),
+        false => format!("{:.*}", decimals, v),
+    }
+}
+
 fn render_stat<B: Backend>(
     f: &mut Frame<B>,
     app: &mut App,
@@ -307,7 +437,7 @@ fn render_stat<B: Backend>(
 ) {
     let border_style = app.get_border_style_from_id(ActiveBlock::Stats);
     let text = match stat {
-        Some(v) => format!("{:.2}", v),
+        Some(v) => result_text(v, 2),
         None => "n/a".to_string(),
     };
     let paragraph = Paragraph::new(text)
@@ -328,6 +458,16 @@ fn render_main<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect)
         Tool::Welcome => render_welcome(f, app, layout_chunk),
         Tool::Chart => render_chart(f, app, layout_chunk),
         Tool::Cube => render_cube(f, app, layout_chunk),
+        Tool::Solver => render_solver(f, app, layout_chunk),
+        Tool::Cross => render_cross(f, app, layout_chunk),
+        Tool::Memo => render_memo(f, app, layout_chunk),
+        Tool::Fmc => render_fmc(f, app, layout_chunk),
+        Tool::Filter => render_filter(f, app, layout_chunk),
+        Tool::Patterns => render_patterns(f, app, layout_chunk),
+        Tool::Virtual => render_virtual(f, app, layout_chunk),
+        Tool::Recon => render_recon(f, app, layout_chunk),
+        Tool::LastLayer => render_last_layer(f, app, layout_chunk),
+        Tool::Cube2 => render_inspection(f, app, layout_chunk),
     }
 }
 
@@ -346,10 +486,90 @@ fn render_welcome<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rec
 
 fn render_cube<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
     let border_style = app.get_border_style_from_id(ActiveBlock::Main);
-    let paragraph = Paragraph::new(CUBE_TEXT)
+    let painting = app.typing();
+    let painter = &app.painter;
+    let cursor = match painting {
+        true => net_facelet(painter.cursor.0, painter.cursor.1),
+        false => None,
+    };
+    let state = match (painter.square1, app.playback) {
+        (Some(v), _) => Some(PuzzleState::Square1(v)),
+        (None, Some(done)) => app.scramble.state_after(done),
+        (None, None) => app.state.clone(),
+    };
+    let shown = match (&painter.state, &state) {
+        (Some(v), _) | (None, Some(PuzzleState::Cube(v))) => Some(NxnState::from(v)),
+        (None, Some(PuzzleState::Nxn(v))) => Some(v.clone()),
+        (None, None) if painting => Some(NxnState::from(&CubeState::solved())),
+        _ => None,
+    };
+    // Below the cube: the check result and, while painting, two hint lines
+    let width = layout_chunk.width.saturating_sub(2) as usize;
+    let height = layout_chunk.height.saturating_sub(5 + 2 * painting as u16) as usize;
+    let view = match (painter.view, &shown) {
+        (CubeView::Net, Some(v)) if net_width(v.n) > width => CubeView::Front,
+        (view, _) => view,
+    };
+    let mut text = match (&shown, &state) {
+        (Some(v), _) if view == CubeView::Net => cube_net(v, cursor),
+        (Some(v), _) => cube_iso(v, view == CubeView::Back, width, height),
+        (None, Some(PuzzleState::Pyraminx(v))) => pyraminx_net(v, width, height),
+        (None, Some(PuzzleState::Skewb(v))) => skewb_net(v, width, height),
+        (None, Some(PuzzleState::Megaminx(v))) => megaminx_net(v, width, height),
+        (None, Some(PuzzleState::Square1(v))) => square1_layers(v, width, height),
+        _ => vec![
+            Spans::from(""),
+            Spans::from(format!(" No preview for {} yet", app.puzzle)),
+        ],
+    };
+    let square1 = app.puzzle == Puzzle::Square1;
+    match (&painter.error, painter.checked) {
+        (Some(e), _) => text.push(Spans::from(Span::styled(
+            format!(" Not a Square-1 scramble: {}", e),
+            Style::default().fg(Color::Red),
+        ))),
+        (None, Some(Ok(()))) => text.push(Spans::from(Span::styled(
+            " Solvable, open the Solver tool to solve it",
+            Style::default().fg(Color::Green),
+        ))),
+        (None, Some(Err(e))) => text.push(Spans::from(Span::styled(
+            format!(" Not solvable: {}", e),
+            Style::default().fg(Color::Red),
+        ))),
+        (None, None) => text.push(Spans::from("")),
+    }
+    if painting && square1 {
+        let input_style = app.get_highlight_style_from_id(ActiveBlock::Main);
+        text.push(Spans::from(vec![
+            Span::raw(" > "),
+            Span::styled(painter.input.clone(), input_style),
+        ]));
+        text.push(Spans::from(
+            " Enter a scramble like (1,0)/ (-1,2)/ to see it, or nothing for the scramble",
+        ));
+    } else if painting {
+        text.push(Spans::from(
+            " hjkl move, w y g r o b paint, enter check and solve, x back to the scramble, v view",
+        ));
+        text.push(Spans::from(
+            " Left and right step through the scramble, up and down jump to its start or end",
+        ));
+    }
+    let title = match (painter.state, painter.square1) {
+        (Some(_), _) => "Cube (painted".to_string(),
+        (None, Some(_)) => "Cube (entered Square-1".to_string(),
+        (None, None) => format!("Cube ({}", app.puzzle),
+    };
+    // Only cubes have a 3D view
+    let title = match (view, &shown) {
+        (CubeView::Front, Some(_)) => format!("{}, 3D)", title),
+        (CubeView::Back, Some(_)) => format!("{}, 3D from behind)", title),
+        _ => format!("{})", title),
+    };
+    let paragraph = Paragraph::new(text)
         .block(
             Block::default()
-                .title("Cube")
+                .title(title)
                 .borders(Borders::ALL)
                 .border_style(border_style),
         )
@@ -357,31 +577,1156 @@ fn render_cube<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect)
     f.render_widget(paragraph, layout_chunk);
 }
 
+fn render_solver<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
+    let border_style = app.get_border_style_from_id(ActiveBlock::Main);
+    let solver = &app.solver;
+    let target = match (solver.custom, &solver.entered) {
+        (Some(_), Some(v)) => format!(
+            "entered scramble {} ({} HTM)",
+            moves_to_string(v),
+            Metric::Htm.count(v)
+        ),
+        (Some(_), None) => "entered cube".to_string(),
+        (None, _) => "current scramble".to_string(),
+    };
+    let mut lines = vec![
+        Spans::from(""),
+        Spans::from(format!(" Solving the {}", target)),
+        Spans::from(""),
+    ];
+    if let Some(e) = &solver.error {
+        lines.push(Spans::from(Span::styled(
+            format!(" {}", e),
+            Style::default().fg(Color::Red),
+        )));
+    } else if let Some(v) = &solver.solution {
+        let counts: Vec<String> = METRICS
+            .iter()
+            .map(|m| format!("{} {}", m.count(v), m))
+            .collect();
+        lines.push(Spans::from(format!(" Solution ({}):", counts.join(", "))));
+        lines.push(Spans::from(format!(" {}", moves_to_string(v))));
+    } else if solver.searching() {
+        lines.push(Spans::from(" Searching..."));
+    }
+    lines.push(Spans::from(""));
+    let input_style = app.get_highlight_style_from_id(ActiveBlock::Main);
+    lines.push(Spans::from(vec![
+        Span::raw(" > "),
+        Span::styled(solver.input.clone(), input_style),
+    ]));
+    lines.push(Spans::from(
+        " Enter a scramble or facelet string (URFDLB order) to solve it, or nothing for the scramble",
+    ));
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Solver")
+                .borders(Borders::ALL)
+                .border_style(border_style),
+        )
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, layout_chunk);
+}
+
+fn render_cross<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
+    let border_style = app.get_border_style_from_id(ActiveBlock::Main);
+    let mut lines = vec![Spans::from("")];
+    match &app.crosses {
+        Some(v) => {
+            lines.push(Spans::from(" Current scramble"));
+            lines.extend(cross_lines(v));
+        }
+        None => lines.push(Spans::from(format!(
+            " Crosses are only worked out for 3x3, not {}",
+            app.puzzle
+        ))),
+    }
+    if let Some((scramble, crosses)) = &app.last_crosses {
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(format!(" Previous scramble: {}", scramble)));
+        lines.extend(cross_lines(crosses));
+    }
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Optimal crosses")
+                .borders(Borders::ALL)
+                .border_style(border_style),
+        )
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, layout_chunk);
+}
+
+/// One line per color: its name in its color, the move count and the moves,
+/// starting with the rotation that puts the color on the bottom.
+fn cross_lines(crosses: &[CrossSolution]) -> Vec<Spans<'static>> {
+    crosses
+        .iter()
+        .map(|v| {
+            let moves = match v.rotation {
+                Some(r) => format!("{} {}", r, moves_to_string(&v.moves)),
+                None => moves_to_string(&v.moves),
+            };
+            Spans::from(vec![
+                Span::raw("   "),
+                Span::styled(
+                    format!("{:<7}", color_name(v.color)),
+                    Style::default().fg(face_color(v.color)),
+                ),
+                Span::raw(format!("{} moves  {}", v.moves.len(), moves)),
+            ])
+        })
+        .collect()
+}
+
+fn render_inspection<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
+    let border_style = app.get_border_style_from_id(ActiveBlock::Main);
+    let mut lines = vec![Spans::from("")];
+    match (&app.inspection, &app.optimal) {
+        (Some(v), _) => {
+            lines.push(Spans::from(" Current scramble"));
+            lines.extend(inspection_lines(v));
+        }
+        // Pyraminx and Skewb get just their optimal solution
+        (None, Some(v)) => {
+            lines.push(Spans::from(" Current scramble"));
+            lines.push(Spans::from(format!(
+                "   Optimal {} moves  {}",
+                v.len(),
+                v.join(" ")
+            )));
+        }
+        (None, None) => lines.push(Spans::from(format!(
+            " Inspection is only worked out for 2x2, Pyraminx and Skewb, not {}",
+            app.puzzle
+        ))),
+    }
+    if let Some((scramble, inspection)) = &app.last_inspection {
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(format!(" Previous scramble: {}", scramble)));
+        lines.extend(inspection_lines(inspection));
+    }
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Inspection")
+                .borders(Borders::ALL)
+                .border_style(border_style),
+        )
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, layout_chunk);
+}
+
+/// The optimal solution, then one line per first layer color: its name in
+/// its color, the move count, the moves and the face the layer ends up on.
+fn inspection_lines(inspection: &Inspection) -> Vec<Spans<'static>> {
+    let mut lines = vec![Spans::from(format!(
+        "   Optimal {} moves  {}",
+        inspection.solution.len(),
+        join_moves(&inspection.solution)
+    ))];
+    lines.push(Spans::from("   First layers"));
+    lines.extend(inspection.layers.iter().map(|v| {
+        Spans::from(vec![
+            Span::raw("     "),
+            Span::styled(
+                format!("{:<7}", color_name(v.color)),
+                Style::default().fg(face_color(v.color)),
+            ),
+            Span::raw(format!(
+                "{} moves  {} (on {})",
+                v.moves.len(),
+                join_moves(&v.moves),
+                v.face
+            )),
+        ])
+    }));
+    lines
+}
+
+fn join_moves<T: std::fmt::Display>(moves: &[T]) -> String {
+    moves
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn render_memo<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
+    let border_style = app.get_border_style_from_id(ActiveBlock::Main);
+    let memo_tool = &app.memo_tool;
+    let lettering = &memo_tool.lettering;
+    let mut lines = vec![Spans::from("")];
+    match &app.memo {
+        Some(v) => {
+            lines.push(Spans::from(" Current scramble"));
+            lines.extend(memo_lines(v, lettering));
+        }
+        None => lines.push(Spans::from(format!(
+            " Memo is only traced for 3x3, not {}",
+            app.puzzle
+        ))),
+    }
+    if let Some((scramble, memo)) = &app.last_memo {
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(format!(" Previous scramble: {}", scramble)));
+        lines.extend(memo_lines(memo, lettering));
+    }
+    lines.push(Spans::from(""));
+    let scheme = match lettering.is_speffz() {
+        true => "Speffz".to_string(),
+        false => lettering.to_string(),
+    };
+    lines.push(Spans::from(format!(
+        " Lettering: {}, buffers UFR and UF",
+        scheme
+    )));
+    if let Some(e) = memo_tool.error {
+        lines.push(Spans::from(Span::styled(
+            format!(" Not a lettering scheme: {}", e),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    let input_style = app.get_highlight_style_from_id(ActiveBlock::Main);
+    lines.push(Spans::from(vec![
+        Span::raw(" > "),
+        Span::styled(memo_tool.input.clone(), input_style),
+    ]));
+    lines.push(Spans::from(
+        " Enter 24 letters in Speffz order (48 for corners then edges) to change the lettering, or nothing for Speffz",
+    ));
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Blindfolded memo")
+                .borders(Borders::ALL)
+                .border_style(border_style),
+        )
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, layout_chunk);
+}
+
+fn render_patterns<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
+    let border_style = app.get_border_style_from_id(ActiveBlock::Main);
+    let block = Block::default()
+        .title("Patterns")
+        .borders(Borders::ALL)
+        .border_style(border_style);
+    let inner = block.inner(layout_chunk);
+    f.render_widget(block, layout_chunk);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(24), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let selected_style = app.get_highlight_style_from_id(ActiveBlock::Main);
+    let patterns = &mut app.patterns;
+    let items: Vec<ListItem> = patterns
+        .list
+        .iter()
+        .map(|v| ListItem::new(format!(" {}", v.name)))
+        .collect();
+    let list = List::new(items).highlight_style(selected_style);
+    f.render_stateful_widget(list, chunks[0], &mut patterns.state);
+
+    let mut lines = match patterns.selected() {
+        Some(v) => {
+            let mut lines = vec![
+                Spans::from(""),
+                Spans::from(format!(
+                    " {} ({} HTM)",
+                    moves_to_string(&v.moves),
+                    Metric::Htm.count(&v.moves)
+                )),
+            ];
+            lines.extend(cube_net(&NxnState::from(&v.state()), None));
+            lines
+        }
+        None => vec![],
+    };
+    lines.push(Spans::from(""));
+    if let Some(e) = &patterns.error {
+        lines.push(Spans::from(Span::styled(
+            format!(" Not a pattern: {}", e),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    lines.push(Spans::from(vec![
+        Span::raw(" > "),
+        Span::styled(
+            patterns.input.clone(),
+            app.get_highlight_style_from_id(ActiveBlock::Main),
+        ),
+    ]));
+    lines.push(Spans::from(
+        " Type `name: moves` to add a pattern",
+    ));
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, chunks[1]);
+}
+
+fn render_filter<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
+    let border_style = app.get_border_style_from_id(ActiveBlock::Main);
+    let tool = &app.filter;
+    let filter = &tool.filter;
+    let on_off = |v: bool| match v {
+        true => "on",
+        false => "off",
+    };
+    let stats = &tool.stats;
+    let lines = vec![
+        Spans::from(""),
+        Spans::from(format!(
+            " Filter {}, for random 3x3 scrambles outside the daily set",
+            on_off(tool.enabled)
+        )),
+        Spans::from(""),
+        Spans::from(format!(
+            "   Reject cubes solvable in fewer than {} moves",
+            filter.min_length
+        )),
+        Spans::from(format!(
+            "   Reject a solved cross of any color: {}",
+            on_off(filter.cross)
+        )),
+        Spans::from(format!(
+            "   Reject a solved 2x2x2 block: {}",
+            on_off(filter.block)
+        )),
+        Spans::from(""),
+        Spans::from(format!(
+            " Checked {} cubes, rejected {}: {} too short, {} with a cross, {} with a block",
+            stats.checked,
+            stats.rejected(),
+            stats.too_short,
+            stats.solved_cross,
+            stats.solved_block
+        )),
+        Spans::from(""),
+        Spans::from(vec![
+            Span::raw(" > "),
+            Span::styled(
+                tool.input.clone(),
+                app.get_highlight_style_from_id(ActiveBlock::Main),
+            ),
+        ]),
+        Spans::from(format!(
+            " f filter on or off, c crosses, b blocks, a number and enter for the minimum length (up to {})",
+            MAX_MIN_LENGTH
+        )),
+    ];
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Scramble filter")
+                .borders(Borders::ALL)
+                .border_style(border_style),
+        )
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, layout_chunk);
+}
+
+fn render_fmc<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
+    let border_style = app.get_border_style_from_id(ActiveBlock::Main);
+    let fmc = &app.fmc;
+    let mut lines = vec![Spans::from("")];
+    if app.puzzle != Puzzle::Fmc {
+        lines.push(Spans::from(format!(
+            " Switch to FMC with p for fewest moves attempts, not {}",
+            app.puzzle
+        )));
+    } else {
+        let status = match fmc.remaining() {
+            Some(_) => format!(" {} left to solve: {}", fmc.countdown(), app.scramble),
+            None => " Press space to start an hour on the scramble".to_string(),
+        };
+        lines.push(Spans::from(status));
+    }
+    if let Some(v) = &fmc.solution {
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(format!(
+            " Last solution ({} OBTM): {}",
+            Metric::Obtm.count(v),
+            moves_to_string(v)
+        )));
+    }
+    lines.push(Spans::from(""));
+    if let Some(e) = &fmc.error {
+        lines.push(Spans::from(Span::styled(
+            format!(" {}", e),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    let input_style = app.get_highlight_style_from_id(ActiveBlock::Main);
+    lines.push(Spans::from(vec![
+        Span::raw(" > "),
+        Span::styled(fmc.input.clone(), input_style),
+    ]));
+    lines.push(Spans::from(
+        " Enter the solution in WCA notation: face and wide turns and rotations, no slices",
+    ));
+    lines.push(Spans::from(" Enter DNF to give up the attempt"));
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Fewest moves")
+                .borders(Borders::ALL)
+                .border_style(border_style),
+        )
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, layout_chunk);
+}
+
+fn render_virtual<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
+    let border_style = app.get_border_style_from_id(ActiveBlock::Main);
+    let vc = &app.virtual_cube;
+    let mut lines = match &vc.state {
+        Some(v) => {
+            let mut lines = cube_net(&NxnState::from(v), None);
+            lines.push(Spans::from(format!(
+                " {} moves: {}",
+                vc.moves.len(),
+                moves_to_string(&vc.moves)
+            )));
+            lines
+        }
+        None => vec![
+            Spans::from(""),
+            Spans::from(format!(
+                " The virtual cube turns 3x3 scrambles, not {}",
+                app.puzzle
+            )),
+        ],
+    };
+    if let Some(v) = vc.solves.last() {
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(format!(
+            " Last solve: {:.3}s, {} moves, {:.2} TPS ({} this session)",
+            v.time,
+            v.count(),
+            v.tps(),
+            vc.solves.len()
+        )));
+        lines.push(Spans::from(format!(" {}", moves_to_string(&v.moves))));
+    }
+    lines.push(Spans::from(""));
+    if let Some(e) = &vc.error {
+        lines.push(Spans::from(Span::styled(
+            format!(" Not a key map: {}", e),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    if vc.editing {
+        lines.push(Spans::from(vec![
+            Span::raw(" > "),
+            Span::styled(
+                vc.input.clone(),
+                app.get_highlight_style_from_id(ActiveBlock::Main),
+            ),
+        ]));
+        lines.push(Spans::from(
+            " Type bindings like `J=U F=U'`, enter adds them to the keys",
+        ));
+    } else {
+        lines.push(Spans::from(
+            " The first move starts the timer, solving stops it. Backspace starts over",
+        ));
+        lines.push(Spans::from(format!(" Keys (enter to change): {}", vc.keymap)));
+    }
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Virtual cube")
+                .borders(Borders::ALL)
+                .border_style(border_style),
+        )
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, layout_chunk);
+}
+
+fn render_recon<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
+    let border_style = app.get_border_style_from_id(ActiveBlock::Main);
+    let fmc = app.puzzle == Puzzle::Fmc;
+    let mut lines = vec![Spans::from("")];
+    match app.recon_target() {
+        Some(i) => {
+            let time = &app.times.times[i];
+            let single = match (fmc, time.time == DNF) {
+                (_, true) => "DNF".to_string(),
+                (true, false) => format!("{:.0} moves", time.time),
+                (false, false) => format!("{:.2}s", time.time),
+            };
+            lines.push(Spans::from(format!(" Solve {}: {}", i + 1, single)));
+            lines.push(Spans::from(format!(
+                " Scramble: {}",
+                time.scramble.as_deref().unwrap_or("not kept")
+            )));
+            lines.push(Spans::from(""));
+            match &time.recon {
+                Some(recon) => {
+                    let total = recon.count();
+                    // Steps get a TPS column once any says how long it took
+                    let timed = !fmc && recon.steps.iter().any(|v| v.time.is_some());
+                    for step in &recon.steps {
+                        let share = match total {
+                            0 => 0.0,
+                            n => 100.0 * step.count() as f64 / n as f64,
+                        };
+                        let tps = match (timed, step.tps()) {
+                            (false, _) => String::new(),
+                            (true, Some(v)) => format!("{:>5.2} TPS  ", v),
+                            (true, None) => format!("{:>9}  ", "-"),
+                        };
+                        lines.push(Spans::from(format!(
+                            " {:<10}{:>4} {}  {:>5.1}%  {}{}",
+                            step.name,
+                            step.count(),
+                            RECON_METRIC,
+                            share,
+                            tps,
+                            moves_to_string(&step.moves)
+                        )));
+                    }
+                    let tps = match fmc || time.time <= 0.0 || time.time == DNF {
+                        true => String::new(),
+                        false => format!(", {:.2} TPS", total as f64 / time.time),
+                    };
+                    lines.push(Spans::from(format!(
+                        " {:<10}{:>4} {}{}",
+                        "Total", total, RECON_METRIC, tps
+                    )));
+                }
+                None => lines.push(Spans::from(" No reconstruction yet")),
+            }
+        }
+        None => lines.push(Spans::from(" No solves to reconstruct yet")),
+    }
+    lines.push(Spans::from(""));
+    if let Some(e) = &app.recon.error {
+        lines.push(Spans::from(Span::styled(
+            format!(" {}", e),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    lines.push(Spans::from(vec![
+        Span::raw(" > "),
+        Span::styled(
+            app.recon.input.clone(),
+            app.get_highlight_style_from_id(ActiveBlock::Main),
+        ),
+    ]));
+    lines.push(Spans::from(
+        " Enter the solution, in steps like `cross: D R2; f2l1: U R U' R'` if you like",
+    ));
+    lines.push(Spans::from(
+        " A step's seconds after its name, like `cross (1.2s): D R2`, give its TPS",
+    ));
+    lines.push(Spans::from(
+        " It goes with the solve selected in the times table, or the latest",
+    ));
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Reconstruction")
+                .borders(Borders::ALL)
+                .border_style(border_style),
+        )
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, layout_chunk);
+}
+
+fn render_last_layer<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
+    let border_style = app.get_border_style_from_id(ActiveBlock::Main);
+    let tool = &app.last_layer;
+    let (label, state) = match (&tool.entered, &app.state) {
+        (Some((label, state)), _) => (label.clone(), Some(*state)),
+        (None, Some(PuzzleState::Cube(v))) => ("current scramble".to_string(), Some(*v)),
+        (None, _) => ("current scramble".to_string(), None),
+    };
+    let mut lines = vec![Spans::from(""), Spans::from(format!(" Cube: {}", label))];
+    match state {
+        Some(mut state) => {
+            lines.extend(cube_net(&NxnState::from(&state), None));
+            match ll::oll_case(&state) {
+                Some(oll) => {
+                    lines.push(Spans::from(case_line("OLL", &oll)));
+                    state.apply_all(&oll.moves());
+                    if let Some(pll) = ll::pll_case(&state) {
+                        lines.push(Spans::from(case_line("PLL", &pll)));
+                    }
+                }
+                None => lines.push(Spans::from(
+                    " F2L is not solved, so there is no last layer case",
+                )),
+            }
+        }
+        None => lines.push(Spans::from(format!(
+            " Last layer cases are only recognized on 3x3, not {}",
+            app.puzzle
+        ))),
+    }
+    lines.push(Spans::from(""));
+    if let Some(e) = &tool.error {
+        lines.push(Spans::from(Span::styled(
+            format!(" {}", e),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    lines.push(Spans::from(vec![
+        Span::raw(" > "),
+        Span::styled(
+            tool.input.clone(),
+            app.get_highlight_style_from_id(ActiveBlock::Main),
+        ),
+    ]));
+    lines.push(Spans::from(
+        " Enter a case like `OLL 27` or `T`, moves from solved, or nothing for the scramble",
+    ));
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Last layer")
+                .borders(Borders::ALL)
+                .border_style(border_style),
+        )
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, layout_chunk);
+}
+
+/// The step, the case's name and what to do for it.
+fn case_line(step: &str, case: &Case) -> String {
+    let moves = match case.moves().is_empty() {
+        true => "nothing to do".to_string(),
+        false => moves_to_string(&case.moves()),
+    };
+    format!(" {}  {:<10}{}", step, case.name, moves)
+}
+
+/// The rotation to hold the cube upright, then the corner and edge memo
+/// with each cycle in brackets, the pieces twisted in place and parity.
+fn memo_lines(memo: &Memo, lettering: &Lettering) -> Vec<Spans<'static>> {
+    let rotation = match memo.rotation.is_empty() {
+        true => "none".to_string(),
+        false => moves_to_string(&memo.rotation),
+    };
+    let mut lines = vec![Spans::from(format!("   Rotation  {}", rotation))];
+    let pieces = [
+        (
+            "Corners",
+            "Twisted",
+            &memo.corners,
+            &lettering.corners,
+            &CORNER_NAMES[..],
+            &["", " clockwise", " counterclockwise"][..],
+        ),
+        (
+            "Edges",
+            "Flipped",
+            &memo.edges,
+            &lettering.edges,
+            &EDGE_NAMES[..],
+            &["", ""][..],
+        ),
+    ];
+    for (name, twisted_name, trace, letters, names, twists) in pieces {
+        lines.push(Spans::from(format!(
+            "   {:<9} {}  {}",
+            name,
+            trace.memo(letters),
+            cycles(trace, letters)
+        )));
+        if !trace.twisted.is_empty() {
+            let twisted: Vec<String> = trace
+                .twisted
+                .iter()
+                .map(|(p, k)| format!("{}{}", names[*p], twists[*k as usize]))
+                .collect();
+            lines.push(Spans::from(format!(
+                "   {:<9} {}",
+                twisted_name,
+                twisted.join(", ")
+            )));
+        }
+    }
+    let parity = match memo.parity() {
+        true => "yes",
+        false => "no",
+    };
+    lines.push(Spans::from(format!("   Parity    {}", parity)));
+    lines
+}
+
+fn cycles(trace: &Trace, letters: &[char; 24]) -> String {
+    trace
+        .cycles
+        .iter()
+        .map(|v| format!("({})", v.iter().map(|i| letters[*i]).collect::<String>()))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Lays the cube out as the usual cross-shaped net, U on top of L F R B with
+/// D underneath. The sticker at facelet index `cursor` is drawn hatched.
+fn cube_net(cube: &NxnState, cursor: Option<usize>) -> Vec<Spans<'static>> {
+    let n = cube.n;
+    // Drop the gaps between stickers on big cubes so the net still fits
+    let sticker = if n <= 5 { "██ " } else { "██" };
+    let gap = Span::raw(" ".repeat(n * sticker.chars().count() + 1));
+    let mut lines = vec![Spans::from("")];
+    for row in 0..n {
+        let mut spans = vec![Span::raw(" "), gap.clone()];
+        spans.extend(face_row(cube, Face::U, row, sticker, cursor));
+        lines.push(Spans::from(spans));
+    }
+    lines.push(Spans::from(""));
+    for row in 0..n {
+        let mut spans = vec![Span::raw(" ")];
+        for face in [Face::L, Face::F, Face::R, Face::B] {
+            spans.extend(face_row(cube, face, row, sticker, cursor));
+            spans.push(Span::raw(" "));
+        }
+        lines.push(Spans::from(spans));
+    }
+    lines.push(Spans::from(""));
+    for row in 0..n {
+        let mut spans = vec![Span::raw(" "), gap.clone()];
+        spans.extend(face_row(cube, Face::D, row, sticker, cursor));
+        lines.push(Spans::from(spans));
+    }
+    lines
+}
+
+/// Columns `cube_net` needs, including the margin.
+fn net_width(n: usize) -> usize {
+    let sticker = if n <= 5 { 3 } else { 2 };
+    1 + 4 * (n * sticker + 1)
+}
+
+/// Draws the cube in isometric 3D as seen from above its front right
+/// corner, showing U, F and R, or turned over with `x2 y'` to show D, L and
+/// B. The drawing fills `width` columns and `height` rows as far as it can
+/// while staying a cube. Each character is two square pixels stacked with
+/// half blocks.
+fn cube_iso(cube: &NxnState, back: bool, width: usize, height: usize) -> Vec<Spans<'static>> {
+    let n = cube.n;
+    let mut cube = cube.clone();
+    if back {
+        for (face, prime, half_turn) in [(Face::R, false, true), (Face::U, true, false)] {
+            cube.apply(NxnMove {
+                face,
+                width: n,
+                prime,
+                half_turn,
+            });
+        }
+    }
+    let cos = 3f64.sqrt() / 2.0;
+    let size = n as f64;
+    // Pixels per sticker. The cube spans 2 cos(30°) n pixels across and 2n
+    // down, with its front corner at the middle.
+    let scale = (width as f64 / (2.0 * cos * size)).min(height as f64 / size);
+    // Thin black lines between stickers, at least half a pixel wide
+    let gap = (0.5 / scale).clamp(0.08, 0.25);
+    let sticker = |face: Face, row: f64, col: f64| {
+        if !(0.0..size).contains(&row) || !(0.0..size).contains(&col) {
+            return None;
+        }
+        let edge = |v: f64| v.fract() < gap || v.fract() > 1.0 - gap;
+        Some(match edge(row) || edge(col) {
+            true => Color::Black,
+            false => face_color(cube.face(face)[row as usize * n + col as usize]),
+        })
+    };
+    let pixel = |x: usize, y: usize| {
+        // Screen position in sticker units: across is x - z and down is
+        // (x + z) / 2 - y, for a point (x, y, z) with the UFR corner at
+        // (n, n, n)
+        let across = (x as f64 + 0.5 - width as f64 / 2.0) / (cos * scale);
+        let down = (y as f64 + 0.5 - height as f64) / scale;
+        let up_x = across / 2.0 + down + size;
+        let up_z = down + size - across / 2.0;
+        let front_x = across + size;
+        let right_z = size - across;
+        sticker(Face::U, up_z, up_x)
+            .or_else(|| sticker(Face::F, down + (size - front_x) / 2.0, front_x))
+            .or_else(|| sticker(Face::R, down + (size - right_z) / 2.0, size - right_z))
+    };
+    half_blocks(width, height, pixel)
+}
+
+/// A picture `width` cells across and `height` down, two square pixels to a
+/// cell, with `pixel` giving the color at each pixel or `None` to leave it
+/// blank.
+fn half_blocks(
+    width: usize,
+    height: usize,
+    pixel: impl Fn(usize, usize) -> Option<Color>,
+) -> Vec<Spans<'static>> {
+    let mut lines = vec![Spans::from("")];
+    for row in 0..height {
+        let mut spans: Vec<Span<'static>> = vec![];
+        let mut run = String::new();
+        let mut style = None;
+        for col in 0..width {
+            let (symbol, next) = match (pixel(col, 2 * row), pixel(col, 2 * row + 1)) {
+                (None, None) => (' ', Style::default()),
+                (Some(top), None) => ('▀', Style::default().fg(top)),
+                (None, Some(bottom)) => ('▄', Style::default().fg(bottom)),
+                (Some(top), Some(bottom)) if top == bottom => ('█', Style::default().fg(top)),
+                (Some(top), Some(bottom)) => ('▀', Style::default().fg(top).bg(bottom)),
+            };
+            if style != Some(next) && !run.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut run), style.unwrap_or_default()));
+            }
+            style = Some(next);
+            run.push(symbol);
+        }
+        spans.push(Span::styled(run, style.unwrap_or_default()));
+        lines.push(Spans::from(spans));
+    }
+    lines
+}
+
+/// Pyraminx faces as triangles: F pointing up between L and R, and D under
+/// it.
+fn pyraminx_net(pyraminx: &PyraminxState, width: usize, height: usize) -> Vec<Spans<'static>> {
+    let h = 3f64.sqrt() / 2.0;
+    // Pixels per edge. The net spans two edges across and two triangles'
+    // heights down.
+    let scale = (width as f64 / 2.0).min(height as f64 / h);
+    let gap = (0.5 * 3.0 / scale).clamp(0.06, 0.2);
+    // Apex, left and right corners of each face in `pyraminx::FACES` order
+    let corners = [
+        [(1.0, 0.0), (0.5, h), (1.5, h)],
+        [(0.5, h), (0.0, 0.0), (1.0, 0.0)],
+        [(1.5, h), (1.0, 0.0), (2.0, 0.0)],
+        [(1.0, 2.0 * h), (0.5, h), (1.5, h)],
+    ];
+    let pixel = |x: usize, y: usize| {
+        let (x, y) = ((x as f64 + 0.5) / scale, (y as f64 + 0.5) / scale);
+        corners.iter().enumerate().find_map(|(f, [apex, left, right])| {
+            // Rows down from the apex and places across, in thirds of an
+            // edge, by solving p = apex + i (left - apex) + j (right - left)
+            let (ax, ay) = (left.0 - apex.0, left.1 - apex.1);
+            let (bx, by) = (right.0 - left.0, right.1 - left.1);
+            let (px, py) = (x - apex.0, y - apex.1);
+            let det = ax * by - ay * bx;
+            let i = 3.0 * (px * by - py * bx) / det;
+            let j = 3.0 * (ax * py - ay * px) / det;
+            if !(0.0..3.0).contains(&i) || j < 0.0 || j > i {
+                return None;
+            }
+            // A blank line between faces
+            if (3.0 - i).min(j).min(i - j) < 2.0 * gap {
+                return Some(None);
+            }
+            let (fi, fj) = (i.fract(), j.fract());
+            let edge = |v: f64| v < gap || v > 1.0 - gap;
+            if edge(fi) || edge(fj) || (fi - fj).abs() < gap {
+                return Some(Some(Color::Black));
+            }
+            let sticker = (i as usize).pow(2) + 2 * j as usize + (fj > fi) as usize;
+            Some(Some(sticker_color(pyraminx.face(f)[sticker])))
+        })?
+    };
+    let width = (2.0 * scale).ceil() as usize;
+    half_blocks(width, height, pixel)
+}
+
+/// Skewb faces laid out like the cube net, each a diamond center with a
+/// corner sticker in each of its corners.
+fn skewb_net(skewb: &SkewbState, width: usize, height: usize) -> Vec<Spans<'static>> {
+    // Pixels per face, with four faces across and three down
+    let scale = (width as f64 / 4.0).min(height as f64 * 2.0 / 3.0);
+    let gap = (1.0 / scale).clamp(0.04, 0.12);
+    // Column and row of each face in `skewb::FACES` order
+    let places = [(1, 0), (2, 1), (1, 1), (1, 2), (0, 1), (3, 1)];
+    let pixel = |x: usize, y: usize| {
+        let (x, y) = ((x as f64 + 0.5) / scale, (y as f64 + 0.5) / scale);
+        let f = places.iter().position(|v| *v == (x as usize, y as usize))?;
+        // Right and down from the middle of the face, from -1 to 1
+        let (u, v) = (2.0 * x.fract() - 1.0, 2.0 * y.fract() - 1.0);
+        // A blank line between faces
+        if u.abs().max(v.abs()) > 1.0 - 2.0 * gap {
+            return None;
+        }
+        if (u.abs() + v.abs() - 1.0).abs() < gap {
+            return Some(Color::Black);
+        }
+        let sticker = match (u.abs() + v.abs() < 1.0, u < 0.0, v < 0.0) {
+            (true, _, _) => 0,
+            (false, true, true) => 1,
+            (false, false, true) => 2,
+            (false, false, false) => 3,
+            (false, true, false) => 4,
+        };
+        Some(sticker_color(skewb.face(f)[sticker]))
+    };
+    let width = (4.0 * scale).ceil() as usize;
+    half_blocks(width, height, pixel)
+}
+
+/// The Megaminx as two flowers of six faces: U with the faces around it,
+/// seen from above with the back up, and D with the faces around it, seen
+/// from below with the front up.
+fn megaminx_net(megaminx: &MegaminxState, width: usize, height: usize) -> Vec<Spans<'static>> {
+    let turned = |(x, y): (f64, f64), degrees: f64| {
+        let (sin, cos) = f64::to_radians(degrees).sin_cos();
+        (x * cos - y * sin, x * sin + y * cos)
+    };
+    // Each face with a face next to it, where its center is drawn and the
+    // way to that face on the screen
+    let flower = |center: usize, down: usize, x: f64| {
+        let around = megaminx::neighbours(center);
+        let first = around.iter().position(|v| *v == down).unwrap_or(0);
+        let mut faces = vec![(center, down, (x, 0.0), (0.0, 1.0))];
+        for (k, f) in around.iter().enumerate() {
+            let (dx, dy) = turned((0.0, 1.0), 72.0 * (k + 5 - first) as f64);
+            let d = 2.0 * megaminx::EDGE_DISTANCE;
+            faces.push((*f, center, (x + d * dx, d * dy), (-dx, -dy)));
+        }
+        faces
+    };
+    let corner_distance = megaminx::EDGE_DISTANCE / f64::cos(std::f64::consts::PI / 5.0);
+    let mut faces = flower(0, 1, 0.0);
+    let corners: Vec<(f64, f64)> = faces
+        .iter()
+        .flat_map(|(_, _, c, dir)| {
+            (0..5).map(move |k| {
+                let (x, y) = turned(*dir, 36.0 + 72.0 * k as f64);
+                (c.0 + corner_distance * x, c.1 + corner_distance * y)
+            })
+        })
+        .collect();
+    let min = |v: fn(&(f64, f64)) -> f64| corners.iter().map(v).fold(f64::MAX, f64::min);
+    let max = |v: fn(&(f64, f64)) -> f64| corners.iter().map(v).fold(f64::MIN, f64::max);
+    let (left, top, right, bottom) = (min(|v| v.0), min(|v| v.1), max(|v| v.0), max(|v| v.1));
+    // D's flower is the same shape, beside U's
+    faces.extend(flower(6, 9, 2.0 * right + 0.2));
+    let right = 3.0 * right + 0.2;
+    // Pixels per unit, with a line between stickers at least half a pixel
+    // wide
+    let scale = (width as f64 / (right - left)).min(2.0 * height as f64 / (bottom - top));
+    let gap = (0.25 / scale).max(0.015);
+    let pixel = |x: usize, y: usize| {
+        let (x, y) = (
+            (x as f64 + 0.5) / scale + left,
+            (y as f64 + 0.5) / scale + top,
+        );
+        faces.iter().find_map(|(f, toward, c, dir)| {
+            let (u, v) = (x - c.0, y - c.1);
+            let along = u * dir.0 + v * dir.1;
+            let across = v * dir.0 - u * dir.1;
+            let p = megaminx::face_point(*f, *toward, along, across);
+            let (sticker, line) = megaminx::sticker_at(*f, p)?;
+            Some(match line < gap {
+                true => Color::Black,
+                false => sticker_color(megaminx.face(*f)[sticker]),
+            })
+        })
+    };
+    let width = ((right - left) * scale).ceil() as usize;
+    let mut lines = half_blocks(width, height, pixel);
+    lines.push(Spans::from(" U and the faces around it, then D from below"));
+    lines
+}
+
+/// Side of the cube each twelfth of a Square-1 layer is on when solved,
+/// going clockwise from the back end of the slice seen from above.
+const SQUARE1_SIDES: [Face; 12] = [
+    Face::B,
+    Face::R,
+    Face::R,
+    Face::R,
+    Face::F,
+    Face::F,
+    Face::F,
+    Face::L,
+    Face::L,
+    Face::L,
+    Face::B,
+    Face::B,
+];
+
+/// The Square-1's top layer seen from above with the back up, and its
+/// bottom layer seen from below with the front up, each piece colored on
+/// top and with its sides round the rim.
+fn square1_layers(square1: &Square1, width: usize, height: usize) -> Vec<Spans<'static>> {
+    // Pixels across each layer, and between the two
+    let gap = 4.0;
+    let size = ((width as f64 - gap) / 2.0)
+        .min(height as f64 * 2.0)
+        .floor();
+    // Half the side of a layer in a square shape, leaving the corners room
+    let r = size / 2.0 / 2f64.sqrt() - 0.5;
+    let solved = Square1::solved();
+    let pixel = |x: usize, y: usize| {
+        let bottom = x as f64 >= size + gap / 2.0;
+        let left = if bottom { size + gap } else { 0.0 };
+        let (u, v) = (
+            x as f64 + 0.5 - left - size / 2.0,
+            y as f64 + 0.5 - size / 2.0,
+        );
+        let distance = u.hypot(v);
+        // Degrees clockwise from the back seen from above, for both layers
+        let angle = u.atan2(-v).to_degrees();
+        let angle = if bottom { 180.0 - angle } else { angle };
+        let slots = if bottom {
+            &square1.bottom
+        } else {
+            &square1.top
+        };
+        let slot = ((angle - 15.0).rem_euclid(360.0) / 30.0) as usize % 12;
+        let piece = slots[slot];
+        let corner = piece < 8;
+        let start = match corner && slots[(slot + 11) % 12] == piece {
+            true => (slot + 11) % 12,
+            false => slot,
+        };
+        let along = (angle - 15.0 - 30.0 * start as f64).rem_euclid(360.0);
+        // The outside of each piece is flat, on a side of the cube when
+        // solved
+        let facing = match (corner, along < 30.0) {
+            (false, _) => 15.0,
+            (true, true) => -15.0,
+            (true, false) => 75.0,
+        };
+        let reach = r / (along - facing).to_radians().cos();
+        if distance > reach {
+            return None;
+        }
+        let span = if corner { 60.0 } else { 30.0 };
+        if distance * along.min(span - along).to_radians().sin() < 0.5 {
+            return Some(Color::Black);
+        }
+        let home_top = solved.top.contains(&piece);
+        if distance < reach * 0.7 {
+            return Some(face_color(if home_top { Face::U } else { Face::D }));
+        }
+        let home = match home_top {
+            true => solved.top.iter().position(|v| *v == piece),
+            false => solved.bottom.iter().position(|v| *v == piece),
+        }?;
+        // `/` turns pieces over, so in the other layer a corner's sides
+        // come the other way round
+        let half = (along >= 30.0) as usize;
+        let half = if corner && home_top == bottom {
+            1 - half
+        } else {
+            half
+        };
+        Some(face_color(SQUARE1_SIDES[home + half]))
+    };
+    let mut lines = half_blocks(
+        (2.0 * size + gap) as usize,
+        (size / 2.0).ceil() as usize,
+        pixel,
+    );
+    lines.push(Spans::from(match square1.flipped {
+        true => " Top, bottom from below, middle flipped",
+        false => " Top, bottom from below, middle square",
+    }));
+    lines
+}
+
+fn face_row(
+    cube: &NxnState,
+    face: Face,
+    row: usize,
+    sticker: &'static str,
+    cursor: Option<usize>,
+) -> Vec<Span<'static>> {
+    let n = cube.n;
+    let first = FACELET_ORDER.iter().position(|v| *v == face).unwrap_or(0) * n * n + row * n;
+    cube.face(face)[row * n..(row + 1) * n]
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let symbol = match cursor == Some(first + i) {
+                true => "▒▒ ",
+                false => sticker,
+            };
+            Span::styled(symbol, Style::default().fg(face_color(*v)))
+        })
+        .collect()
+}
+
+/// Standard WCA color scheme: white top, green front.
+fn face_color(face: Face) -> Color {
+    match face {
+        Face::U => Color::White,
+        Face::L => Color::Indexed(208),
+        Face::F => Color::Green,
+        Face::R => Color::Red,
+        Face::B => Color::Blue,
+        Face::D => Color::Yellow,
+        Face::None => Color::DarkGray,
+    }
+}
+
+/// Terminal color of a sticker of a puzzle other than a cube.
+fn sticker_color(color: StickerColor) -> Color {
+    match color {
+        StickerColor::White => Color::White,
+        StickerColor::Orange => Color::Indexed(208),
+        StickerColor::Green => Color::Green,
+        StickerColor::Red => Color::Red,
+        StickerColor::Blue => Color::Blue,
+        StickerColor::Yellow => Color::Yellow,
+        StickerColor::Purple => Color::Indexed(93),
+        StickerColor::Gray => Color::Gray,
+        StickerColor::LightYellow => Color::Indexed(229),
+        StickerColor::Pink => Color::Indexed(213),
+        StickerColor::LightGreen => Color::Indexed(118),
+        StickerColor::LightBlue => Color::Indexed(117),
+    }
+}
+
+/// Name of the standard color scheme's color for a face.
+fn color_name(face: Face) -> &'static str {
+    match face {
+        Face::U => "White",
+        Face::L => "Orange",
+        Face::F => "Green",
+        Face::R => "Red",
+        Face::B => "Blue",
+        Face::D => "Yellow",
+        Face::None => "None",
+    }
+}
+
 fn render_chart<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect) {
     let singles = app
         .times
         .times
         .iter()
         .enumerate()
+        .filter(|(_, v)| v.time != DNF)
         .map(|(i, v)| (i as f64, v.time))
         .collect::<Vec<(f64, f64)>>();
     let ao5s = &app
         .times
         .iter()
         .enumerate()
-        .filter_map(|(i, v)| match v.ao5 {
-            Some(a) => Some((i as f64, a)),
-            None => None,
-        })
+        .filter_map(|(i, v)| v.ao5.filter(|a| *a != DNF).map(|a| (i as f64, a)))
         .collect::<Vec<(f64, f64)>>();
     let ao12s = &app
         .times
         .iter()
         .enumerate()
-        .filter_map(|(i, v)| match v.ao12 {
-            Some(a) => Some((i as f64, a)),
-            None => None,
-        })
+        .filter_map(|(i, v)| v.ao12.filter(|a| *a != DNF).map(|a| (i as f64, a)))
         .collect::<Vec<(f64, f64)>>();
 
     let border_style = app.get_border_style_from_id(ActiveBlock::Main);
@@ -397,13 +1742,13 @@ fn render_chart<B: Backend>(f: &mut Frame<B>, app: &mut App, layout_chunk: Rect)
             .marker(symbols::Marker::Dot)
             .graph_type(GraphType::Line)
             .style(Style::default().fg(Color::LightGreen))
-            .data(&ao5s),
+            .data(ao5s),
         Dataset::default()
             .name("ao12")
             .marker(symbols::Marker::Dot)
             .graph_type(GraphType::Line)
             .style(Style::default().fg(Color::Magenta))
-            .data(&ao12s),
+            .data(ao12s),
     ];
 
     let xmid = app.times.times.len() / 2;